@@ -0,0 +1,306 @@
+//! Command-line arguments, for the first launch and for launches forwarded
+//! to the running instance:
+//!
+//! ```text
+//! contextpad [--readonly] [--new-tab] [FILE[:LINE[:COLUMN]]]...
+//! contextpad --diff OLD NEW
+//! ```
+//!
+//! and for the commands that run without a window:
+//!
+//! ```text
+//! contextpad render --template ID [--var NAME=VALUE]...
+//! contextpad formula FORMULA < INPUT
+//! contextpad tokens [--model MODEL] [FILE]...
+//! ```
+
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::Path;
+
+pub const HEADLESS_USAGE: &str = "\
+usage: contextpad render --template ID [--var NAME=VALUE]...
+       contextpad formula FORMULA < INPUT
+       contextpad tokens [--model MODEL] [FILE]...";
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+pub struct OpenFile {
+    pub path: String,
+    // One-based position to put the cursor at
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+}
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+pub struct DiffPaths {
+    pub old: String,
+    pub new: String,
+}
+
+/// A path from the command line that can't be opened.
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+pub struct OpenFailure {
+    // As given on the command line
+    pub path: String,
+    pub reason: String,
+}
+
+/// What a launch asked for.
+#[derive(serde::Serialize, Clone, Debug, Default, PartialEq)]
+pub struct OpenRequest {
+    pub files: Vec<OpenFile>,
+    // Open the files in read-only tabs
+    pub readonly: bool,
+    // Open another tab even when a file is already open
+    pub new_tab: bool,
+    pub diff: Option<DiffPaths>,
+}
+
+impl OpenRequest {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.diff.is_none()
+    }
+
+    /// Makes every path absolute and canonical, resolving relative ones against
+    /// `cwd`, the directory the launch came from. Paths that can't be opened
+    /// are taken out of the request and returned as failures.
+    pub fn resolve(mut self, cwd: &Path) -> (Self, Vec<OpenFailure>) {
+        let mut failures = Vec::new();
+        let mut fail = |path: &str, reason: String| {
+            failures.push(OpenFailure {
+                path: path.to_string(),
+                reason,
+            })
+        };
+
+        let files = std::mem::take(&mut self.files);
+        for mut file in files {
+            match resolve_path(&file.path, cwd) {
+                Ok(path) => {
+                    file.path = path;
+                    self.files.push(file);
+                }
+                Err(reason) => fail(&file.path, reason),
+            }
+        }
+
+        if let Some(diff) = self.diff.take() {
+            match (resolve_path(&diff.old, cwd), resolve_path(&diff.new, cwd)) {
+                (Ok(old), Ok(new)) => self.diff = Some(DiffPaths { old, new }),
+                (old, new) => {
+                    if let Err(reason) = old {
+                        fail(&diff.old, reason);
+                    }
+                    if let Err(reason) = new {
+                        fail(&diff.new, reason);
+                    }
+                }
+            }
+        }
+
+        (self, failures)
+    }
+}
+
+fn resolve_path(path: &str, cwd: &Path) -> Result<String, String> {
+    let given = Path::new(path);
+    let joined = if given.is_absolute() { given.to_path_buf() } else { cwd.join(given) };
+    // dunce keeps Windows paths in their usual form rather than \\?\C:\...
+    let resolved = dunce::canonicalize(&joined).map_err(|e| match e.kind() {
+        ErrorKind::NotFound => "File not found".to_string(),
+        ErrorKind::PermissionDenied => "Permission denied".to_string(),
+        _ => e.to_string(),
+    })?;
+    if resolved.is_dir() {
+        return Err("Is a directory".to_string());
+    }
+    Ok(resolved.to_string_lossy().to_string())
+}
+
+// Splits a trailing `:123` off an argument
+fn split_number(arg: &str) -> Option<(&str, u32)> {
+    let (head, tail) = arg.rsplit_once(':')?;
+    if head.is_empty() || tail.is_empty() || !tail.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let number = tail.parse::<u32>().ok()?;
+    Some((head, number.max(1)))
+}
+
+/// `notes.md:120:5` is notes.md at line 120, column 5. Only trailing numbers
+/// are taken, so Windows drive letters (`C:\notes.md:12`) stay in the path.
+fn parse_file(arg: &str) -> OpenFile {
+    match split_number(arg) {
+        Some((rest, last)) => match split_number(rest) {
+            Some((path, line)) => OpenFile {
+                path: path.to_string(),
+                line: Some(line),
+                column: Some(last),
+            },
+            None => OpenFile {
+                path: rest.to_string(),
+                line: Some(last),
+                column: None,
+            },
+        },
+        None => OpenFile {
+            path: arg.to_string(),
+            line: None,
+            column: None,
+        },
+    }
+}
+
+/// Parses arguments after the program name.
+pub fn parse_args<I, S>(args: I) -> Result<OpenRequest, String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut request = OpenRequest::default();
+    let mut args = args.into_iter();
+    let mut options_done = false;
+
+    while let Some(arg) = args.next() {
+        let arg = arg.as_ref();
+        if options_done || !arg.starts_with('-') || arg == "-" {
+            // Deep links arrive as arguments too; the deep-link handler takes those
+            if !arg.contains("://") {
+                request.files.push(parse_file(arg));
+            }
+            continue;
+        }
+
+        match arg {
+            "--" => options_done = true,
+            "--readonly" | "-r" => request.readonly = true,
+            "--new-tab" | "-n" => request.new_tab = true,
+            "--diff" | "-d" => {
+                let (Some(old), Some(new)) = (args.next(), args.next()) else {
+                    return Err("--diff needs two files: --diff OLD NEW".to_string());
+                };
+                request.diff = Some(DiffPaths {
+                    old: old.as_ref().to_string(),
+                    new: new.as_ref().to_string(),
+                });
+            }
+            // Process serial number macOS adds when launched from Finder
+            _ if arg.starts_with("-psn_") => {}
+            _ => return Err(format!("Unknown option {}", arg)),
+        }
+    }
+
+    Ok(request)
+}
+
+/// A command that prints its result instead of opening the editor.
+#[derive(Debug, PartialEq)]
+pub enum Headless {
+    Render {
+        // Template id, or its name
+        template: String,
+        variables: HashMap<String, String>,
+    },
+    Formula {
+        formula: String,
+    },
+    Tokens {
+        // None for the model picked in the app's settings
+        model: Option<String>,
+        // Standard input when empty
+        files: Vec<String>,
+    },
+}
+
+fn option_value<I, S>(option: &str, args: &mut I) -> Result<String, String>
+where
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
+    args.next()
+        .map(|value| value.as_ref().to_string())
+        .ok_or_else(|| format!("{} needs a value", option))
+}
+
+fn parse_render<I, S>(mut args: I) -> Result<Headless, String>
+where
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut template = None;
+    let mut variables = HashMap::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--template" | "-t" => template = Some(option_value("--template", &mut args)?),
+            "--var" | "-v" => {
+                let assignment = option_value("--var", &mut args)?;
+                let Some((name, value)) = assignment.split_once('=').filter(|(name, _)| !name.is_empty()) else {
+                    return Err(format!("--var needs NAME=VALUE, got {}", assignment));
+                };
+                variables.insert(name.to_string(), value.to_string());
+            }
+            other => return Err(format!("Unexpected argument {}", other)),
+        }
+    }
+
+    let template = template.ok_or("render needs --template ID")?;
+    Ok(Headless::Render { template, variables })
+}
+
+fn parse_formula<I, S>(mut args: I) -> Result<Headless, String>
+where
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
+    let formula = args.next().ok_or("formula needs a formula, e.g. '=UPPER()'")?;
+    if let Some(extra) = args.next() {
+        return Err(format!("Unexpected argument {}; the input is read from standard input", extra.as_ref()));
+    }
+    // Written as in a cell, =UPPER(), or as in the editor's actions, UPPER()
+    let formula = formula.as_ref().trim();
+    let formula = formula.strip_prefix('=').unwrap_or(formula);
+    Ok(Headless::Formula {
+        formula: formula.to_string(),
+    })
+}
+
+fn parse_tokens<I, S>(mut args: I) -> Result<Headless, String>
+where
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut model = None;
+    let mut files = Vec::new();
+    let mut options_done = false;
+
+    while let Some(arg) = args.next() {
+        let arg = arg.as_ref();
+        match arg {
+            _ if options_done || !arg.starts_with('-') || arg == "-" => files.push(arg.to_string()),
+            "--" => options_done = true,
+            "--model" | "-m" => model = Some(option_value("--model", &mut args)?),
+            _ => return Err(format!("Unknown option {}", arg)),
+        }
+    }
+
+    Ok(Headless::Tokens { model, files })
+}
+
+/// The headless command the arguments after the program name ask for, or
+/// None when they're for the editor. A file named like a command is opened
+/// with `contextpad -- render`.
+pub fn parse_headless<I, S>(args: I) -> Option<Result<Headless, String>>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut args = args.into_iter();
+    let command = args.next()?;
+    match command.as_ref() {
+        "render" => Some(parse_render(args)),
+        "formula" => Some(parse_formula(args)),
+        "tokens" => Some(parse_tokens(args)),
+        _ => None,
+    }
+}
@@ -0,0 +1,362 @@
+use similar::{capture_diff_slices_deadline, group_diff_ops, Algorithm, ChangeTag, DiffOp, TextDiff};
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+
+use crate::encoding;
+
+// Past this, Myers stops looking for the minimal diff and settles for a coarser one
+const DIFF_TIMEOUT: Duration = Duration::from_secs(2);
+const DEFAULT_CONTEXT_LINES: usize = 3;
+
+#[derive(serde::Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffKind {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// A run of text within a changed line.
+#[derive(serde::Serialize)]
+pub struct WordChange {
+    kind: DiffKind,
+    text: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct DiffLine {
+    kind: DiffKind,
+    // Zero-based line numbers in the old and new text
+    old_line: Option<usize>,
+    new_line: Option<usize>,
+    text: String,
+    // For a changed line paired with its counterpart on the other side:
+    // which words are shared and which are this side's own
+    #[serde(skip_serializing_if = "Option::is_none")]
+    words: Option<Vec<WordChange>>,
+}
+
+#[derive(serde::Serialize)]
+pub struct DiffHunk {
+    // One-based, with unified-diff semantics (an empty range starts at the line before)
+    old_start: usize,
+    old_lines: usize,
+    new_start: usize,
+    new_lines: usize,
+    lines: Vec<DiffLine>,
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+pub struct DiffOptions {
+    ignore_whitespace: bool,
+    ignore_case: bool,
+    // Unchanged lines kept around each hunk (default 3)
+    context_lines: Option<usize>,
+    // Skip the word-level breakdown of changed lines
+    lines_only: bool,
+    // Also render the result as unified diff text
+    unified: bool,
+    // File names for the unified header; default to the paths compared
+    old_label: Option<String>,
+    new_label: Option<String>,
+}
+
+// Lines keep their terminator so a missing final newline shows up as a change
+fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+fn line_key<'a>(line: &'a str, options: &DiffOptions) -> Cow<'a, str> {
+    let mut key = Cow::Borrowed(line);
+    if options.ignore_whitespace {
+        key = Cow::Owned(key.chars().filter(|c| !c.is_whitespace()).collect());
+    }
+    if options.ignore_case {
+        key = Cow::Owned(key.to_lowercase());
+    }
+    key
+}
+
+fn diff_ops(old: &[&str], new: &[&str], options: &DiffOptions) -> Vec<DiffOp> {
+    let old_keys: Vec<Cow<str>> = old.iter().map(|line| line_key(line, options)).collect();
+    let new_keys: Vec<Cow<str>> = new.iter().map(|line| line_key(line, options)).collect();
+    capture_diff_slices_deadline(
+        Algorithm::Myers,
+        &old_keys,
+        &new_keys,
+        Some(Instant::now() + DIFF_TIMEOUT),
+    )
+}
+
+fn line(kind: DiffKind, old_line: Option<usize>, new_line: Option<usize>, text: &str) -> DiffLine {
+    DiffLine {
+        kind,
+        old_line,
+        new_line,
+        text: text.trim_end_matches('\n').to_string(),
+        words: None,
+    }
+}
+
+// Word runs for each side of a changed line pair, merged into runs of one kind
+fn word_changes(old: &str, new: &str) -> (Vec<WordChange>, Vec<WordChange>) {
+    let diff = TextDiff::from_words(old.trim_end_matches('\n'), new.trim_end_matches('\n'));
+    let (mut old_words, mut new_words): (Vec<WordChange>, Vec<WordChange>) = (Vec::new(), Vec::new());
+
+    let push = |words: &mut Vec<WordChange>, kind: DiffKind, text: &str| match words.last_mut() {
+        Some(last) if last.kind == kind => last.text.push_str(text),
+        _ => words.push(WordChange { kind, text: text.to_string() }),
+    };
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Equal => {
+                push(&mut old_words, DiffKind::Equal, change.value());
+                push(&mut new_words, DiffKind::Equal, change.value());
+            }
+            ChangeTag::Delete => push(&mut old_words, DiffKind::Delete, change.value()),
+            ChangeTag::Insert => push(&mut new_words, DiffKind::Insert, change.value()),
+        }
+    }
+
+    (old_words, new_words)
+}
+
+fn op_lines(op: &DiffOp, old: &[&str], new: &[&str], words: bool, out: &mut Vec<DiffLine>) {
+    let old_range = op.old_range();
+    let new_range = op.new_range();
+
+    if let DiffOp::Equal { .. } = op {
+        for (i, j) in old_range.zip(new_range) {
+            out.push(line(DiffKind::Equal, Some(i), Some(j), old[i]));
+        }
+        return;
+    }
+
+    let deleted_from = out.len();
+    out.extend(old_range.clone().map(|i| line(DiffKind::Delete, Some(i), None, old[i])));
+    let inserted_from = out.len();
+    out.extend(new_range.clone().map(|j| line(DiffKind::Insert, None, Some(j), new[j])));
+
+    // Replaced lines are paired up in order for the word breakdown
+    if words && matches!(op, DiffOp::Replace { .. }) {
+        for (k, (i, j)) in old_range.zip(new_range).enumerate() {
+            let (old_words, new_words) = word_changes(old[i], new[j]);
+            out[deleted_from + k].words = Some(old_words);
+            out[inserted_from + k].words = Some(new_words);
+        }
+    }
+}
+
+/// Full line diff with every line of both texts, as used by local history.
+pub(crate) fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old_lines = split_lines(old);
+    let new_lines = split_lines(new);
+    let mut result = Vec::with_capacity(old_lines.len().max(new_lines.len()));
+    for op in diff_ops(&old_lines, &new_lines, &DiffOptions::default()) {
+        op_lines(&op, &old_lines, &new_lines, false, &mut result);
+    }
+    result
+}
+
+// One-based start of a zero-based range, the way unified diffs number them
+fn hunk_start(range: &std::ops::Range<usize>) -> usize {
+    if range.is_empty() {
+        range.start
+    } else {
+        range.start + 1
+    }
+}
+
+fn render_unified(hunks: &[DiffHunk], old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let old_count = split_lines(old).len();
+    let new_count = split_lines(new).len();
+    // Whether this line is the last of its text and has no newline after it
+    let unterminated = |line: &DiffLine| match line.kind {
+        DiffKind::Insert => line.new_line == new_count.checked_sub(1) && !new.ends_with('\n'),
+        _ => line.old_line == old_count.checked_sub(1) && !old.ends_with('\n'),
+    };
+
+    let mut text = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for hunk in hunks {
+        text.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+        ));
+        for line in &hunk.lines {
+            let prefix = match line.kind {
+                DiffKind::Equal => ' ',
+                DiffKind::Insert => '+',
+                DiffKind::Delete => '-',
+            };
+            text.push(prefix);
+            text.push_str(&line.text);
+            text.push('\n');
+            if unterminated(line) {
+                text.push_str("\\ No newline at end of file\n");
+            }
+        }
+    }
+    text
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffInput {
+    Text(String),
+    Path(String),
+}
+
+impl DiffInput {
+    // Content plus the label used in unified output
+    fn load(self, fallback_label: &str) -> Result<(String, String), String> {
+        match self {
+            DiffInput::Text(text) => Ok((text, fallback_label.to_string())),
+            DiffInput::Path(path) => {
+                let bytes = std::fs::read(&path)
+                    .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+                Ok((encoding::decode(&bytes).content, path))
+            }
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct DiffResult {
+    identical: bool,
+    pub(crate) insertions: usize,
+    pub(crate) deletions: usize,
+    hunks: Vec<DiffHunk>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unified: Option<String>,
+}
+
+/// Diffs two texts. The labels name each side in unified output unless the
+/// options override them.
+pub(crate) fn compute(old: &str, new: &str, options: &DiffOptions, old_label: &str, new_label: &str) -> DiffResult {
+    let old_lines = split_lines(old);
+    let new_lines = split_lines(new);
+    let ops = diff_ops(&old_lines, &new_lines, options);
+
+    let mut insertions = 0;
+    let mut deletions = 0;
+    for op in &ops {
+        if !matches!(op, DiffOp::Equal { .. }) {
+            deletions += op.old_range().len();
+            insertions += op.new_range().len();
+        }
+    }
+
+    let context = options.context_lines.unwrap_or(DEFAULT_CONTEXT_LINES);
+    let hunks: Vec<DiffHunk> = group_diff_ops(ops, context)
+        .into_iter()
+        .map(|group| {
+            let old_range = group[0].old_range().start..group[group.len() - 1].old_range().end;
+            let new_range = group[0].new_range().start..group[group.len() - 1].new_range().end;
+            let mut lines = Vec::new();
+            for op in &group {
+                op_lines(op, &old_lines, &new_lines, !options.lines_only, &mut lines);
+            }
+            DiffHunk {
+                old_start: hunk_start(&old_range),
+                old_lines: old_range.len(),
+                new_start: hunk_start(&new_range),
+                new_lines: new_range.len(),
+                lines,
+            }
+        })
+        .collect();
+
+    let unified = options.unified.then(|| {
+        let old_label = options.old_label.as_deref().unwrap_or(old_label);
+        let new_label = options.new_label.as_deref().unwrap_or(new_label);
+        render_unified(&hunks, old, new, old_label, new_label)
+    });
+
+    DiffResult {
+        identical: hunks.is_empty(),
+        insertions,
+        deletions,
+        hunks,
+        unified,
+    }
+}
+
+/// Diffs two texts or files (each side is `{ "text": ... }` or `{ "path": ... }`).
+/// Returns hunks of changed lines with surrounding context, word-level
+/// changes for replaced lines, and optionally a unified diff.
+#[tauri::command]
+pub async fn compute_diff(
+    old: DiffInput,
+    new: DiffInput,
+    options: Option<DiffOptions>,
+) -> Result<DiffResult, String> {
+    let options = options.unwrap_or_default();
+    let (old, old_label) = old.load("original")?;
+    let (new, new_label) = new.load("modified")?;
+
+    tauri::async_runtime::spawn_blocking(move || compute(&old, &new, &options, &old_label, &new_label))
+        .await
+        .map_err(|e| format!("Diff failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unified(old: &str, new: &str) -> String {
+        let options = DiffOptions { unified: true, ..DiffOptions::default() };
+        compute(old, new, &options, "old", "new").unified.unwrap()
+    }
+
+    #[test]
+    fn counts_changed_lines() {
+        let result = compute("a\nb\nc\n", "a\nB\nc\nd\n", &DiffOptions::default(), "old", "new");
+        assert!(!result.identical);
+        assert_eq!((result.insertions, result.deletions), (2, 1));
+        assert_eq!(result.hunks.len(), 1);
+    }
+
+    #[test]
+    fn ignores_whitespace() {
+        let (old, new) = ("a b\nc\n", "ab\n  c\t\n");
+        assert!(!compute(old, new, &DiffOptions::default(), "old", "new").identical);
+
+        let options = DiffOptions { ignore_whitespace: true, ..DiffOptions::default() };
+        let result = compute(old, new, &options, "old", "new");
+        assert!(result.identical);
+        assert_eq!((result.insertions, result.deletions), (0, 0));
+    }
+
+    #[test]
+    fn ignores_case() {
+        let (old, new) = ("Hello\nWorld\n", "hello\nWORLD\n");
+        assert!(!compute(old, new, &DiffOptions::default(), "old", "new").identical);
+
+        let options = DiffOptions { ignore_case: true, ..DiffOptions::default() };
+        assert!(compute(old, new, &options, "old", "new").identical);
+    }
+
+    #[test]
+    fn marks_new_text_without_final_newline() {
+        assert_eq!(
+            unified("a\nb\n", "a\nb"),
+            "--- old\n+++ new\n@@ -1,2 +1,2 @@\n a\n-b\n+b\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn marks_old_text_without_final_newline() {
+        assert_eq!(
+            unified("a", "a\nb\n"),
+            "--- old\n+++ new\n@@ -1,1 +1,2 @@\n-a\n\\ No newline at end of file\n+a\n+b\n"
+        );
+    }
+
+    #[test]
+    fn same_text_without_final_newline_is_identical() {
+        let result = compute("a\nb", "a\nb", &DiffOptions { unified: true, ..DiffOptions::default() }, "old", "new");
+        assert!(result.identical);
+        assert_eq!(result.unified.as_deref(), Some("--- old\n+++ new\n"));
+    }
+}
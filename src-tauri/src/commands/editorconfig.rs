@@ -0,0 +1,95 @@
+use ec4rs::property::{
+    Charset, EndOfLine, FinalNewline, IndentSize, IndentStyle, MaxLineLen, TabWidth, TrimTrailingWs,
+};
+use std::path::Path;
+
+use crate::encoding::LineEnding;
+
+/// Effective EditorConfig properties for one file. Unset or invalid values
+/// are `None` so the editor can keep its own defaults for them.
+#[derive(Default, serde::Serialize)]
+pub struct EditorConfig {
+    // "tab" or "space"
+    indent_style: Option<String>,
+    indent_size: Option<usize>,
+    tab_width: Option<usize>,
+    pub end_of_line: Option<LineEnding>,
+    charset: Option<String>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub insert_final_newline: Option<bool>,
+    max_line_length: Option<usize>,
+}
+
+pub(crate) fn resolve(path: &Path) -> Result<EditorConfig, String> {
+    let mut properties = ec4rs::properties_of(path)
+        .map_err(|e| format!("Failed to read .editorconfig: {}", e))?;
+    // Fills indent_size from tab_width and vice versa, as the spec requires
+    properties.use_fallbacks();
+
+    Ok(EditorConfig {
+        indent_style: properties.get::<IndentStyle>().ok().map(|style| style.to_string()),
+        indent_size: match properties.get::<IndentSize>() {
+            Ok(IndentSize::Value(size)) => Some(size),
+            _ => None,
+        },
+        tab_width: match properties.get::<TabWidth>() {
+            Ok(TabWidth::Value(width)) => Some(width),
+            _ => None,
+        },
+        end_of_line: properties.get::<EndOfLine>().ok().map(|eol| match eol {
+            EndOfLine::Lf => LineEnding::Lf,
+            EndOfLine::CrLf => LineEnding::Crlf,
+            EndOfLine::Cr => LineEnding::Cr,
+        }),
+        charset: properties.get::<Charset>().ok().map(|charset| charset.to_string()),
+        trim_trailing_whitespace: match properties.get::<TrimTrailingWs>() {
+            Ok(TrimTrailingWs::Value(trim)) => Some(trim),
+            _ => None,
+        },
+        insert_final_newline: match properties.get::<FinalNewline>() {
+            Ok(FinalNewline::Value(insert)) => Some(insert),
+            _ => None,
+        },
+        max_line_length: match properties.get::<MaxLineLen>() {
+            Ok(MaxLineLen::Value(length)) => Some(length),
+            _ => None,
+        },
+    })
+}
+
+/// Applies the whitespace rules that take effect on save. Line endings are
+/// left to the encoder so they can be normalised in one place.
+pub(crate) fn apply_save_rules(text: &str, config: &EditorConfig) -> String {
+    let mut result = if config.trim_trailing_whitespace == Some(true) {
+        text.split('\n')
+            .map(|line| {
+                let (body, cr) = match line.strip_suffix('\r') {
+                    Some(body) => (body, "\r"),
+                    None => (line, ""),
+                };
+                format!("{}{}", body.trim_end_matches([' ', '\t']), cr)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        text.to_string()
+    };
+
+    match config.insert_final_newline {
+        Some(true) if !result.is_empty() && !result.ends_with('\n') && !result.ends_with('\r') => {
+            result.push('\n');
+        }
+        Some(false) => {
+            let trimmed = result.trim_end_matches(['\r', '\n']).len();
+            result.truncate(trimmed);
+        }
+        _ => {}
+    }
+
+    result
+}
+
+#[tauri::command]
+pub fn get_editor_config(path: String) -> Result<EditorConfig, String> {
+    resolve(Path::new(&path))
+}
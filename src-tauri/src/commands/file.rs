@@ -1,18 +1,316 @@
-use tauri::Window;
+use tauri::{State, Window};
+use sha2::{Digest, Sha256};
 use std::fs;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+use super::editorconfig;
+use super::history::{HistoryOptions, HistoryState, SnapshotSource};
+use super::language::detect_from_path;
+use super::watcher::WatcherState;
+use crate::encoding::{self, LineEnding};
+
+pub(crate) fn hash_bytes(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+pub(crate) fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+pub(crate) fn modified_secs(path: &Path) -> Result<u64, String> {
+    let metadata = fs::metadata(path)
+        .map_err(|e| format!("Failed to get file metadata: {}", e))?;
+
+    let modified = metadata.modified()
+        .map_err(|e| format!("Failed to get modified time: {}", e))?;
+
+    let duration = modified.duration_since(SystemTime::UNIX_EPOCH)
+        .map_err(|e| format!("Failed to calculate duration: {}", e))?;
+
+    Ok(duration.as_secs())
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupMode {
+    Rotate,
+    Folder,
+}
+
+#[derive(serde::Deserialize)]
+pub struct BackupOptions {
+    mode: BackupMode,
+    max_backups: usize,
+    workspace_root: Option<String>,
+}
+
+// Writes through a temp file in the same directory so a crash or full disk
+// can never leave the target truncated: either the old or the new content survives
+pub(crate) fn atomic_write(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    // Write through symlinks instead of replacing them with a regular file
+    let is_symlink = fs::symlink_metadata(path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+    let target = if is_symlink { fs::canonicalize(path)? } else { path.to_path_buf() };
+
+    let dir = match target.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let existing_permissions = fs::metadata(&target).ok().map(|m| m.permissions());
+
+    let mut temp = tempfile::Builder::new()
+        .prefix(".contextpad-")
+        .suffix(".tmp")
+        .tempfile_in(&dir)?;
+    temp.write_all(bytes)?;
+    temp.as_file().sync_all()?;
+
+    if let Some(permissions) = existing_permissions {
+        fs::set_permissions(temp.path(), permissions)?;
+    }
+
+    temp.persist(&target).map_err(|e| e.error)?;
+
+    // Make the rename itself durable
+    #[cfg(unix)]
+    if let Ok(dir_handle) = fs::File::open(&dir) {
+        let _ = dir_handle.sync_all();
+    }
+
+    Ok(())
+}
+
+fn rotating_backup_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".bak");
+    if index > 0 {
+        name.push(format!(".{}", index));
+    }
+    PathBuf::from(name)
+}
+
+// notes.md.bak is the newest copy, notes.md.bak.1 the one before, and so on
+fn rotate_backups(path: &Path, keep: usize) -> std::io::Result<()> {
+    let _ = fs::remove_file(rotating_backup_path(path, keep - 1));
+
+    for index in (0..keep - 1).rev() {
+        let from = rotating_backup_path(path, index);
+        if from.exists() {
+            fs::rename(&from, rotating_backup_path(path, index + 1))?;
+        }
+    }
+
+    fs::copy(path, rotating_backup_path(path, 0))?;
+    Ok(())
+}
+
+// Keeps timestamped copies under <workspace>/.contextpad/backups, mirroring the
+// file's location inside the workspace
+fn folder_backup(path: &Path, workspace_root: &Path, keep: usize) -> std::io::Result<()> {
+    let relative = path.strip_prefix(workspace_root)
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "File is outside the workspace"))?;
+    let file_name = path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut backup_dir = workspace_root.join(".contextpad").join("backups");
+    if let Some(parent) = relative.parent() {
+        backup_dir.push(parent);
+    }
+    fs::create_dir_all(&backup_dir)?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    fs::copy(path, backup_dir.join(format!("{}.{}.bak", file_name, timestamp)))?;
+
+    let prefix = format!("{}.", file_name);
+    let mut existing: Vec<(u128, PathBuf)> = fs::read_dir(&backup_dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let stamp = name.strip_prefix(&prefix)?.strip_suffix(".bak")?.parse().ok()?;
+            Some((stamp, entry.path()))
+        })
+        .collect();
+
+    existing.sort_by_key(|(stamp, _)| std::cmp::Reverse(*stamp));
+    for (_, old) in existing.into_iter().skip(keep) {
+        let _ = fs::remove_file(old);
+    }
+
+    Ok(())
+}
+
+fn create_backup(path: &Path, options: &BackupOptions) -> std::io::Result<()> {
+    if options.max_backups == 0 {
+        return Ok(());
+    }
+
+    match (&options.mode, &options.workspace_root) {
+        (BackupMode::Folder, Some(root)) if path.starts_with(root) => {
+            folder_backup(path, Path::new(root), options.max_backups)
+        }
+        // Files outside the workspace fall back to sibling .bak copies
+        _ => rotate_backups(path, options.max_backups),
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct FileContent {
+    content: String,
+    encoding: String,
+    has_bom: bool,
+    line_ending: LineEnding,
+}
+
+impl From<encoding::DecodedText> for FileContent {
+    fn from(decoded: encoding::DecodedText) -> Self {
+        Self {
+            content: decoded.content,
+            encoding: decoded.encoding.name().to_string(),
+            has_bom: decoded.has_bom,
+            line_ending: decoded.line_ending,
+        }
+    }
+}
+
+// Structured so the UI can offer the hex view for binaries instead of
+// opening them as garbled text
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReadFileError {
+    Binary { path: String, size: u64, mime: String, message: String },
+    Io { path: String, message: String },
+}
+
 #[tauri::command]
-pub async fn read_file(path: String) -> Result<String, String> {
-    fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read file: {}", e))
+pub async fn read_file(path: String) -> Result<FileContent, ReadFileError> {
+    let bytes = fs::read(&path).map_err(|e| ReadFileError::Io {
+        path: path.clone(),
+        message: format!("Failed to read file: {}", e),
+    })?;
+
+    let sample = &bytes[..bytes.len().min(PROBE_SAMPLE_SIZE)];
+    if encoding::looks_binary(sample) {
+        return Err(ReadFileError::Binary {
+            mime: guess_mime(sample, Path::new(&path), true),
+            size: bytes.len() as u64,
+            message: format!("{} is a binary file", path),
+            path,
+        });
+    }
+
+    Ok(encoding::decode(&bytes).into())
+}
+
+/// Encodes editor text the way write_file saves it. Defaults to UTF-8
+/// without BOM and the content's own line endings.
+pub(crate) fn encode_content(
+    content: &str,
+    encoding: Option<&str>,
+    has_bom: Option<bool>,
+    line_ending: Option<LineEnding>,
+) -> Result<Vec<u8>, String> {
+    let target_encoding = match encoding {
+        Some(label) => encoding::encoding_for_label(label)?,
+        None => encoding_rs::UTF_8,
+    };
+    let text = match line_ending {
+        Some(eol) => encoding::normalize_line_endings(content, eol),
+        None => content.to_string(),
+    };
+    encoding::encode(&text, target_encoding, has_bom.unwrap_or(false))
+}
+
+/// What write_file changed on the way to disk, so the frontend can bring the
+/// buffer in line with the saved file.
+#[derive(serde::Serialize)]
+pub struct WriteResult {
+    // Set when save-time rules altered the text (newlines normalised to \n)
+    content: Option<String>,
+    line_ending: Option<LineEnding>,
+    hash: String,
 }
 
 #[tauri::command]
-pub async fn write_file(path: String, content: String) -> Result<(), String> {
-    fs::write(&path, content)
-        .map_err(|e| format!("Failed to write file: {}", e))
+#[allow(clippy::too_many_arguments)]
+pub async fn write_file(
+    watcher: State<'_, WatcherState>,
+    snapshots: State<'_, HistoryState>,
+    path: String,
+    content: String,
+    encoding: Option<String>,
+    has_bom: Option<bool>,
+    line_ending: Option<LineEnding>,
+    backup: Option<BackupOptions>,
+    apply_editor_config: Option<bool>,
+    history: Option<HistoryOptions>,
+) -> Result<WriteResult, String> {
+    let path_buf = PathBuf::from(&path);
+    let mut line_ending = line_ending;
+    let mut formatted = None;
+
+    if apply_editor_config.unwrap_or(false) {
+        // A broken .editorconfig shouldn't stop the save
+        match editorconfig::resolve(&path_buf) {
+            Ok(config) => {
+                let text = editorconfig::apply_save_rules(&content, &config);
+                if text != content {
+                    formatted = Some(text);
+                }
+                line_ending = config.end_of_line.or(line_ending);
+            }
+            Err(e) => eprintln!("Skipping EditorConfig for {}: {}", path, e),
+        }
+    }
+    let content = formatted.clone().unwrap_or(content);
+
+    let bytes = encode_content(&content, encoding.as_deref(), has_bom, line_ending)?;
+    let hash = hash_bytes(&bytes);
+
+    if let Some(options) = backup {
+        // Skip the copy when saving unchanged content
+        let unchanged = hash_file(&path_buf).map(|h| h == hash).unwrap_or(true);
+        if !unchanged {
+            if let Err(e) = create_backup(&path_buf, &options) {
+                eprintln!("Failed to create backup for {}: {}", path, e);
+            }
+        }
+    }
+
+    atomic_write(&path_buf, &bytes)
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+
+    watcher.record_hash(&path_buf, hash.clone());
+
+    if let Some(options) = history {
+        if let Err(e) = snapshots.record(&path_buf, &bytes, SnapshotSource::Save, &options) {
+            eprintln!("Failed to record snapshot for {}: {}", path, e);
+        }
+    }
+
+    Ok(WriteResult {
+        content: formatted.map(|text| encoding::normalize_line_endings(&text, LineEnding::Lf)),
+        line_ending,
+        hash,
+    })
 }
 
 #[tauri::command]
@@ -75,52 +373,194 @@ pub fn get_file_name(path: String) -> Result<String, String> {
 
 #[tauri::command]
 pub fn detect_language_from_path(path: String) -> String {
-    let path_buf = PathBuf::from(&path);
-    let extension = path_buf.extension()
+    detect_from_path(Path::new(&path))
+        .map(|detection| detection.language)
+        .unwrap_or_else(|| "text".to_string()) // Default to plain text
+}
+
+#[tauri::command]
+pub fn get_file_modified_time(path: String) -> Result<u64, String> {
+    modified_secs(Path::new(&path))
+}
+
+#[tauri::command]
+pub fn get_file_hash(path: String) -> Result<String, String> {
+    hash_file(Path::new(&path))
+        .map_err(|e| format!("Failed to hash file: {}", e))
+}
+
+const PROBE_SAMPLE_SIZE: usize = 8 * 1024;
+const HEX_BYTES_PER_ROW: usize = 16;
+const MAX_HEX_CHUNK: usize = 64 * 1024;
+
+// Magic numbers for the binary formats users most often drop into the editor
+const MAGIC_MIME_TYPES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xFF\xD8\xFF", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"BM", "image/bmp"),
+    (b"\x00\x00\x01\x00", "image/x-icon"),
+    (b"II*\x00", "image/tiff"),
+    (b"MM\x00*", "image/tiff"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1F\x8B", "application/gzip"),
+    (b"7z\xBC\xAF\x27\x1C", "application/x-7z-compressed"),
+    (b"Rar!\x1A\x07", "application/vnd.rar"),
+    (b"SQLite format 3\x00", "application/vnd.sqlite3"),
+    (b"\x7FELF", "application/x-elf"),
+    (b"MZ", "application/vnd.microsoft.portable-executable"),
+    (b"\x00asm", "application/wasm"),
+    (b"ID3", "audio/mpeg"),
+    (b"OggS", "audio/ogg"),
+    (b"fLaC", "audio/flac"),
+];
+
+fn guess_mime(sample: &[u8], path: &Path, is_binary: bool) -> String {
+    // Short signatures like "BM" or "MZ" are only trusted for binary content
+    if is_binary {
+        if sample.len() >= 12 && &sample[..4] == b"RIFF" {
+            match &sample[8..12] {
+                b"WEBP" => return "image/webp".to_string(),
+                b"WAVE" => return "audio/wav".to_string(),
+                b"AVI " => return "video/x-msvideo".to_string(),
+                _ => {}
+            }
+        }
+        if sample.len() >= 12 && &sample[4..8] == b"ftyp" {
+            return "video/mp4".to_string();
+        }
+        if let Some((_, mime)) = MAGIC_MIME_TYPES.iter().find(|(magic, _)| sample.starts_with(magic)) {
+            return mime.to_string();
+        }
+    }
+
+    let extension = path.extension()
         .and_then(|ext| ext.to_str())
-        .unwrap_or("");
-
-    match extension {
-        "md" | "markdown" => "markdown",
-        "txt" => "text",
-        "json" | "jsonc" => "json",
-        "yaml" | "yml" => "yaml",
-        "csv" => "csv",
-        "js" | "jsx" | "mjs" | "cjs" => "javascript",
-        "ts" | "tsx" => "typescript",
-        "py" | "pyw" => "python",
-        "rs" => "rust",
-        "html" | "htm" => "html",
-        "css" | "scss" | "sass" | "less" => "css",
-        "xml" => "xml",
-        "toml" => "toml",
-        "sh" | "bash" => "shell",
-        "c" | "h" => "c",
-        "cpp" | "cc" | "cxx" | "hpp" => "cpp",
-        "java" => "java",
-        "go" => "go",
-        "rb" => "ruby",
-        "php" => "php",
-        "swift" => "swift",
-        "kt" | "kts" => "kotlin",
-        "sql" => "sql",
-        _ => "text", // Default to plain text
+        .map(|ext| ext.to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "md" | "markdown" => "text/markdown",
+        "json" | "jsonc" => "application/json",
+        "yaml" | "yml" => "text/yaml",
+        "xml" => "application/xml",
+        "html" | "htm" => "text/html",
+        "css" | "scss" | "sass" | "less" => "text/css",
+        "js" | "jsx" | "mjs" | "cjs" => "text/javascript",
+        "ts" | "tsx" => "text/typescript",
+        "py" | "pyw" => "text/x-python",
+        "rs" => "text/rust",
+        "sql" => "application/sql",
+        "csv" => "text/csv",
+        "tsv" => "text/tab-separated-values",
+        "svg" => "image/svg+xml",
+        _ if is_binary => "application/octet-stream",
+        _ => "text/plain",
     }
     .to_string()
 }
 
+#[derive(serde::Serialize)]
+pub struct FileProbe {
+    size: u64,
+    is_binary: bool,
+    mime: String,
+    encoding: Option<String>,
+    has_bom: bool,
+    line_ending: Option<LineEnding>,
+}
+
 #[tauri::command]
-pub fn get_file_modified_time(path: String) -> Result<u64, String> {
-    let metadata = fs::metadata(&path)
-        .map_err(|e| format!("Failed to get file metadata: {}", e))?;
+pub fn probe_file(path: String) -> Result<FileProbe, String> {
+    let path_buf = PathBuf::from(&path);
+    let mut file = fs::File::open(&path_buf)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    let size = file.metadata()
+        .map_err(|e| format!("Failed to get file metadata: {}", e))?
+        .len();
+
+    let mut sample = Vec::with_capacity(PROBE_SAMPLE_SIZE);
+    (&mut file).take(PROBE_SAMPLE_SIZE as u64)
+        .read_to_end(&mut sample)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let is_binary = encoding::looks_binary(&sample);
+    let mime = guess_mime(&sample, &path_buf, is_binary);
+
+    if is_binary {
+        return Ok(FileProbe {
+            size,
+            is_binary,
+            mime,
+            encoding: None,
+            has_bom: false,
+            line_ending: None,
+        });
+    }
 
-    let modified = metadata.modified()
-        .map_err(|e| format!("Failed to get modified time: {}", e))?;
+    let (detected, bom_length) = encoding::detect_encoding(&sample);
+    let (text, _) = detected.decode_without_bom_handling(&sample[bom_length..]);
+
+    Ok(FileProbe {
+        size,
+        is_binary,
+        mime,
+        encoding: Some(detected.name().to_string()),
+        has_bom: bom_length > 0,
+        line_ending: Some(encoding::detect_line_ending(&text)),
+    })
+}
 
-    let duration = modified.duration_since(SystemTime::UNIX_EPOCH)
-        .map_err(|e| format!("Failed to calculate duration: {}", e))?;
+#[derive(serde::Serialize)]
+pub struct HexRow {
+    offset: u64,
+    hex: String,
+    ascii: String,
+}
 
-    Ok(duration.as_secs())
+#[derive(serde::Serialize)]
+pub struct HexChunk {
+    offset: u64,
+    total_size: u64,
+    rows: Vec<HexRow>,
+}
+
+#[tauri::command]
+pub fn read_file_hex(path: String, offset: u64, length: usize) -> Result<HexChunk, String> {
+    use std::io::{Seek, SeekFrom};
+
+    let mut file = fs::File::open(&path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    let total_size = file.metadata()
+        .map_err(|e| format!("Failed to get file metadata: {}", e))?
+        .len();
+
+    // Keep rows aligned so paging through the file never shifts columns
+    let offset = offset - offset % HEX_BYTES_PER_ROW as u64;
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let mut bytes = Vec::with_capacity(length.min(MAX_HEX_CHUNK));
+    file.take(length.min(MAX_HEX_CHUNK) as u64)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let rows = bytes
+        .chunks(HEX_BYTES_PER_ROW)
+        .enumerate()
+        .map(|(i, row)| HexRow {
+            offset: offset + (i * HEX_BYTES_PER_ROW) as u64,
+            hex: row.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" "),
+            ascii: row
+                .iter()
+                .map(|b| if b.is_ascii_graphic() || *b == b' ' { *b as char } else { '.' })
+                .collect(),
+        })
+        .collect();
+
+    Ok(HexChunk { offset, total_size, rows })
 }
 
 #[tauri::command]
@@ -142,7 +582,7 @@ pub struct FileNode {
 }
 
 #[tauri::command]
-pub fn read_directory(path: String) -> Result<Vec<FileNode>, String> {
+pub fn read_directory(path: String, show_hidden: Option<bool>) -> Result<Vec<FileNode>, String> {
     let dir_path = PathBuf::from(&path);
 
     if !dir_path.is_dir() {
@@ -159,8 +599,8 @@ pub fn read_directory(path: String) -> Result<Vec<FileNode>, String> {
         let entry_path = entry.path();
         let file_name = entry.file_name().to_string_lossy().to_string();
 
-        // Skip hidden files (starting with .)
-        if file_name.starts_with('.') {
+        // Skip hidden files (starting with .) unless requested; .git is never listed
+        if file_name == ".git" || (file_name.starts_with('.') && !show_hidden.unwrap_or(false)) {
             continue;
         }
 
@@ -192,6 +632,307 @@ pub async fn rename_file(old_path: String, new_path: String) -> Result<(), Strin
         .map_err(|e| format!("Failed to rename file: {}", e))
 }
 
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileOpErrorKind {
+    TargetExists,
+    NotFound,
+    PermissionDenied,
+    InvalidTarget,
+    Io,
+}
+
+// Structured so the UI can offer overwrite/rename instead of just showing text
+#[derive(Debug, serde::Serialize)]
+pub struct FileOpError {
+    kind: FileOpErrorKind,
+    path: String,
+    message: String,
+}
+
+impl FileOpError {
+    fn new(kind: FileOpErrorKind, path: &Path, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            path: path.to_string_lossy().to_string(),
+            message: message.into(),
+        }
+    }
+
+    fn from_io(path: &Path, error: std::io::Error) -> Self {
+        let kind = match error.kind() {
+            std::io::ErrorKind::AlreadyExists => FileOpErrorKind::TargetExists,
+            std::io::ErrorKind::NotFound => FileOpErrorKind::NotFound,
+            std::io::ErrorKind::PermissionDenied => FileOpErrorKind::PermissionDenied,
+            _ => FileOpErrorKind::Io,
+        };
+        Self::new(kind, path, error.to_string())
+    }
+}
+
+fn exists(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+fn ensure_target_free(target: &Path, overwrite: bool) -> Result<(), FileOpError> {
+    if exists(target) && !overwrite {
+        return Err(FileOpError::new(FileOpErrorKind::TargetExists, target, "Target already exists"));
+    }
+    Ok(())
+}
+
+fn ensure_source_exists(source: &Path) -> Result<(), FileOpError> {
+    if !exists(source) {
+        return Err(FileOpError::new(FileOpErrorKind::NotFound, source, "Source does not exist"));
+    }
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+// Canonical form of a path that may not exist yet: its parent resolved, its
+// name kept as given
+fn resolve_target(target: &Path) -> PathBuf {
+    if let Ok(resolved) = dunce::canonicalize(target) {
+        return resolved;
+    }
+    match (target.parent(), target.file_name()) {
+        (Some(parent), Some(name)) => dunce::canonicalize(parent)
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|_| target.to_path_buf()),
+        _ => target.to_path_buf(),
+    }
+}
+
+// Also true for different spellings of one path: `a/./x.md`, a symlinked
+// folder, or `Notes.md` and `notes.md` on a case-insensitive volume
+fn is_same_file(source: &Path, target: &Path) -> bool {
+    same_file::is_same_file(source, target).unwrap_or(false)
+}
+
+// A copy or move onto the source itself would delete it, and one into a
+// folder inside the source would recurse forever
+fn ensure_distinct(source: &Path, target: &Path) -> Result<(), FileOpError> {
+    let invalid = || FileOpError::new(FileOpErrorKind::InvalidTarget, target, "Target is the source or lies inside it");
+    if is_same_file(source, target) {
+        return Err(invalid());
+    }
+    let source_dir = fs::symlink_metadata(source).map(|m| m.is_dir()).unwrap_or(false);
+    let resolved_source = dunce::canonicalize(source).map_err(|e| FileOpError::from_io(source, e))?;
+    if source_dir && resolve_target(target).starts_with(&resolved_source) {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, target: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, target)
+}
+
+#[cfg(windows)]
+fn copy_symlink(source: &Path, target: &Path) -> std::io::Result<()> {
+    let link = fs::read_link(source)?;
+    if fs::metadata(source).map(|m| m.is_dir()).unwrap_or(false) {
+        std::os::windows::fs::symlink_dir(link, target)
+    } else {
+        std::os::windows::fs::symlink_file(link, target)
+    }
+}
+
+// Symlinks are recreated rather than followed, so a link loop or a link to a
+// folder outside the tree doesn't pull that folder into the copy
+fn copy_recursive(source: &Path, target: &Path) -> Result<(), FileOpError> {
+    let metadata = fs::symlink_metadata(source).map_err(|e| FileOpError::from_io(source, e))?;
+    if metadata.file_type().is_symlink() {
+        return copy_symlink(source, target).map_err(|e| FileOpError::from_io(target, e));
+    }
+    if !metadata.is_dir() {
+        fs::copy(source, target).map_err(|e| FileOpError::from_io(target, e))?;
+        return Ok(());
+    }
+
+    fs::create_dir(target).map_err(|e| FileOpError::from_io(target, e))?;
+    let entries = fs::read_dir(source).map_err(|e| FileOpError::from_io(source, e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| FileOpError::from_io(source, e))?;
+        copy_recursive(&entry.path(), &target.join(entry.file_name()))?;
+    }
+
+    Ok(())
+}
+
+/// Where a copy or move is built before it replaces the target: a hidden
+/// folder next to the target, so the final rename stays on one volume.
+/// Whatever is left in it is removed when it's dropped.
+struct Staging {
+    dir: tempfile::TempDir,
+}
+
+impl Staging {
+    fn next_to(target: &Path) -> Result<Self, FileOpError> {
+        let parent = match target.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let dir = tempfile::Builder::new()
+            .prefix(".contextpad-")
+            .tempdir_in(parent)
+            .map_err(|e| FileOpError::from_io(parent, e))?;
+        Ok(Self { dir })
+    }
+
+    fn item(&self) -> PathBuf {
+        self.dir.path().join("item")
+    }
+
+    /// Puts the staged item at `target`. An existing target is set aside
+    /// first and put back if that fails, so it's only gone once the new
+    /// item is in its place.
+    fn replace(&self, target: &Path) -> Result<(), FileOpError> {
+        let item = self.item();
+        if !exists(target) {
+            return fs::rename(&item, target).map_err(|e| FileOpError::from_io(target, e));
+        }
+
+        let previous = self.dir.path().join("previous");
+        fs::rename(target, &previous).map_err(|e| FileOpError::from_io(target, e))?;
+        if let Err(e) = fs::rename(&item, target) {
+            let _ = fs::rename(&previous, target);
+            return Err(FileOpError::from_io(target, e));
+        }
+        // The previous target goes with the staging folder
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub async fn create_file(path: String, content: Option<String>, overwrite: Option<bool>) -> Result<(), FileOpError> {
+    let path_buf = PathBuf::from(&path);
+    ensure_target_free(&path_buf, overwrite.unwrap_or(false))?;
+    let content = content.unwrap_or_default();
+
+    if exists(&path_buf) {
+        // Only an overwrite gets here; the old file stays until the new one is written
+        if path_buf.is_dir() {
+            return Err(FileOpError::new(FileOpErrorKind::InvalidTarget, &path_buf, "Target is a folder"));
+        }
+        return atomic_write(&path_buf, content.as_bytes()).map_err(|e| FileOpError::from_io(&path_buf, e));
+    }
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path_buf)
+        .map_err(|e| FileOpError::from_io(&path_buf, e))?;
+
+    file.write_all(content.as_bytes())
+        .map_err(|e| FileOpError::from_io(&path_buf, e))
+}
+
+#[tauri::command]
+pub async fn create_directory(path: String) -> Result<(), FileOpError> {
+    let path_buf = PathBuf::from(&path);
+    ensure_target_free(&path_buf, false)?;
+
+    fs::create_dir_all(&path_buf)
+        .map_err(|e| FileOpError::from_io(&path_buf, e))
+}
+
+#[tauri::command]
+pub async fn copy_path(source: String, target: String, overwrite: Option<bool>) -> Result<(), FileOpError> {
+    let (source, target) = (PathBuf::from(&source), PathBuf::from(&target));
+    ensure_source_exists(&source)?;
+    ensure_distinct(&source, &target)?;
+    ensure_target_free(&target, overwrite.unwrap_or(false))?;
+
+    // Copied in full before it replaces anything, so a failed copy leaves the
+    // target as it was
+    let staging = Staging::next_to(&target)?;
+    copy_recursive(&source, &staging.item())?;
+    staging.replace(&target)
+}
+
+#[tauri::command]
+pub async fn move_path(source: String, target: String, overwrite: Option<bool>) -> Result<(), FileOpError> {
+    let (source, target) = (PathBuf::from(&source), PathBuf::from(&target));
+    ensure_source_exists(&source)?;
+
+    // Renaming Notes.md to notes.md where case doesn't matter: the target
+    // "exists" only because it's the source
+    if is_same_file(&source, &target) && source.file_name() != target.file_name() {
+        return fs::rename(&source, &target).map_err(|e| FileOpError::from_io(&target, e));
+    }
+    ensure_distinct(&source, &target)?;
+    ensure_target_free(&target, overwrite.unwrap_or(false))?;
+
+    if !exists(&target) {
+        match fs::rename(&source, &target) {
+            Ok(()) => return Ok(()),
+            // Renames can't cross volumes; copy through staging below
+            Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {}
+            Err(e) => return Err(FileOpError::from_io(&target, e)),
+        }
+    }
+
+    let staging = Staging::next_to(&target)?;
+    let copied = match fs::rename(&source, staging.item()) {
+        Ok(()) => false,
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            copy_recursive(&source, &staging.item())?;
+            true
+        }
+        Err(e) => return Err(FileOpError::from_io(&source, e)),
+    };
+
+    if let Err(e) = staging.replace(&target) {
+        // A renamed source goes back where it was
+        if !copied {
+            let _ = fs::rename(staging.item(), &source);
+        }
+        return Err(e);
+    }
+    if copied {
+        remove_path(&source).map_err(|e| FileOpError::from_io(&source, e))?;
+    }
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+pub struct DeleteResult {
+    trashed: bool,
+}
+
+#[tauri::command]
+pub async fn delete_path(path: String, permanent: Option<bool>) -> Result<DeleteResult, FileOpError> {
+    let path_buf = PathBuf::from(&path);
+    ensure_source_exists(&path_buf)?;
+
+    if !permanent.unwrap_or(false) {
+        match trash::delete(&path_buf) {
+            Ok(()) => return Ok(DeleteResult { trashed: true }),
+            // No usable trash (e.g. network drives) - delete permanently below
+            Err(e) => eprintln!("Failed to move {} to trash: {}", path, e),
+        }
+    }
+
+    let result = if path_buf.is_dir() {
+        fs::remove_dir_all(&path_buf)
+    } else {
+        fs::remove_file(&path_buf)
+    };
+    result.map_err(|e| FileOpError::from_io(&path_buf, e))?;
+
+    Ok(DeleteResult { trashed: false })
+}
+
 #[tauri::command]
 pub async fn open_file_explorer(path: String) -> Result<(), String> {
     #[cfg(target_os = "windows")]
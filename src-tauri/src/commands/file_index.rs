@@ -0,0 +1,327 @@
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::overrides::Override;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::walk::{build_overrides, build_walker, build_walker_at, WalkOptions};
+use super::watcher::WatcherState;
+
+const DEFAULT_LIMIT: usize = 50;
+
+struct FileIndex {
+    root: PathBuf,
+    // Paths relative to the root, always '/'-separated
+    files: HashSet<String>,
+    dirs: HashSet<String>,
+    // The rules of the initial walk, applied to paths created after it
+    options: WalkOptions,
+    overrides: Override,
+    exclude_ignore: Gitignore,
+    global_ignore: Gitignore,
+    // Ignore files per directory (relative path), read as paths appear there
+    ignore_files: HashMap<String, Gitignore>,
+}
+
+impl FileIndex {
+    fn build(root: &Path, options: &WalkOptions) -> Result<Self, String> {
+        let mut files = HashSet::new();
+        let mut dirs = HashSet::from([String::new()]);
+
+        for entry in build_walker(root, options)?.build().filter_map(|e| e.ok()) {
+            let Some(relative) = relative_path(root, entry.path()) else { continue };
+            if relative.is_empty() {
+                continue;
+            }
+            if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                dirs.insert(relative);
+            } else {
+                files.insert(relative);
+            }
+        }
+
+        // What the walker applies on top of the ignore files in the tree
+        let (exclude_ignore, global_ignore) = if options.respect_gitignore {
+            (Gitignore::new(root.join(".git").join("info").join("exclude")).0, Gitignore::global().0)
+        } else {
+            (Gitignore::empty(), Gitignore::empty())
+        };
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            files,
+            dirs,
+            options: options.clone(),
+            overrides: build_overrides(root, options)?,
+            exclude_ignore,
+            global_ignore,
+            ignore_files: HashMap::new(),
+        })
+    }
+
+    // The .gitignore and .ignore of one directory, as the walker combines them
+    fn ignore_file(&mut self, dir: &str) -> &Gitignore {
+        let root = &self.root;
+        self.ignore_files.entry(dir.to_string()).or_insert_with(|| {
+            let path = if dir.is_empty() { root.clone() } else { root.join(dir) };
+            let mut builder = GitignoreBuilder::new(&path);
+            builder.add(path.join(".gitignore"));
+            builder.add(path.join(".ignore"));
+            builder.build().unwrap_or_else(|_| Gitignore::empty())
+        })
+    }
+
+    // New paths are only indexed inside directories the walk already accepted,
+    // which keeps ignored trees like node_modules out during installs. The
+    // path itself is held to the same rules as the walk.
+    fn accepts(&mut self, relative: &str, is_dir: bool) -> bool {
+        let (parent, name) = relative.rsplit_once('/').unwrap_or(("", relative));
+        if !self.dirs.contains(parent) || name == ".git" {
+            return false;
+        }
+        if !self.options.show_hidden && name.starts_with('.') {
+            return false;
+        }
+        if self.options.max_depth.is_some_and(|depth| relative.split('/').count() > depth) {
+            return false;
+        }
+
+        let path = self.root.join(relative);
+        if self.overrides.matched(&path, is_dir).is_ignore() {
+            return false;
+        }
+        if !self.options.respect_gitignore {
+            return true;
+        }
+
+        // The closest ignore file with a rule for the path decides
+        let mut dir = Some(parent);
+        while let Some(current) = dir {
+            let matched = self.ignore_file(current).matched(&path, is_dir);
+            if matched.is_ignore() {
+                return false;
+            }
+            if matched.is_whitelist() {
+                return true;
+            }
+            dir = (!current.is_empty()).then(|| current.rsplit_once('/').map_or("", |(up, _)| up));
+        }
+        let repository = self.exclude_ignore.matched(&path, is_dir);
+        if !repository.is_none() {
+            return repository.is_whitelist();
+        }
+        !self.global_ignore.matched(&path, is_dir).is_ignore()
+    }
+
+    // An ignore file that changed is read again the next time it's needed
+    fn forget_ignore_file(&mut self, relative: &str) {
+        let (parent, name) = relative.rsplit_once('/').unwrap_or(("", relative));
+        if name == ".gitignore" || name == ".ignore" {
+            self.ignore_files.remove(parent);
+        }
+    }
+
+    fn add(&mut self, path: &Path) {
+        let Some(relative) = relative_path(&self.root, path) else { return };
+        self.forget_ignore_file(&relative);
+        let is_dir = path.is_dir();
+        if relative.is_empty() || !self.accepts(&relative, is_dir) {
+            return;
+        }
+
+        if !is_dir {
+            self.files.insert(relative);
+            return;
+        }
+
+        // A directory moved into the workspace arrives as a single event
+        self.dirs.insert(relative);
+        let Ok(walker) = build_walker_at(&self.root, path, &self.options) else { return };
+        for entry in walker.build().skip(1).filter_map(|e| e.ok()) {
+            let Some(child) = relative_path(&self.root, entry.path()) else { continue };
+            if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                self.dirs.insert(child);
+            } else {
+                self.files.insert(child);
+            }
+        }
+    }
+
+    fn find(&self, query: &str, limit: usize) -> Vec<FuzzyFileMatch> {
+        let to_match = |relative: &String, score: i64, indices: Vec<usize>| FuzzyFileMatch {
+            path: self.root.join(relative).to_string_lossy().to_string(),
+            relative_path: relative.clone(),
+            score,
+            indices,
+        };
+
+        if query.is_empty() {
+            let mut files: Vec<&String> = self.files.iter().collect();
+            files.sort();
+            return files.into_iter().take(limit).map(|f| to_match(f, 0, Vec::new())).collect();
+        }
+
+        // Like quick open in VS Code, spaces separate terms that may match
+        // anywhere in the path, in any order; every term has to match
+        let terms: Vec<&str> = query.split_whitespace().collect();
+        let matcher = SkimMatcherV2::default().smart_case();
+
+        let mut matches: Vec<(i64, &String, Vec<usize>)> = self
+            .files
+            .iter()
+            .filter_map(|relative| {
+                // Prefer hits in the file name over hits spread across directories
+                let name = relative.rsplit('/').next().unwrap_or(relative);
+                let mut total = 0;
+                let mut indices = Vec::new();
+                for term in &terms {
+                    let (score, term_indices) = matcher.fuzzy_indices(relative, term)?;
+                    total += score + matcher.fuzzy_match(name, term).unwrap_or(0) / 2;
+                    indices.extend(term_indices);
+                }
+                indices.sort_unstable();
+                indices.dedup();
+                Some((total, relative, indices))
+            })
+            .collect();
+
+        matches.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.len().cmp(&b.1.len())).then_with(|| a.1.cmp(b.1)));
+
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(score, relative, indices)| to_match(relative, score, indices))
+            .collect()
+    }
+
+    fn remove(&mut self, path: &Path) {
+        let Some(relative) = relative_path(&self.root, path) else { return };
+        self.forget_ignore_file(&relative);
+        if relative.is_empty() {
+            return;
+        }
+
+        self.files.remove(&relative);
+        if self.dirs.remove(&relative) {
+            let prefix = format!("{}/", relative);
+            self.files.retain(|f| !f.starts_with(&prefix));
+            self.dirs.retain(|d| !d.starts_with(&prefix));
+            self.ignore_files.retain(|d, _| d != &relative && !d.starts_with(&prefix));
+        }
+    }
+}
+
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+#[derive(Default)]
+pub struct FileIndexState {
+    index: RwLock<Option<FileIndex>>,
+    generation: AtomicU64,
+}
+
+impl FileIndexState {
+    /// Applies a filesystem change reported by the watcher.
+    pub fn path_created(&self, path: &Path) {
+        if let Some(index) = self.index.write().unwrap().as_mut() {
+            index.add(path);
+        }
+    }
+
+    pub fn path_removed(&self, path: &Path) {
+        if let Some(index) = self.index.write().unwrap().as_mut() {
+            index.remove(path);
+        }
+    }
+
+    pub fn path_renamed(&self, from: &Path, to: &Path) {
+        if let Some(index) = self.index.write().unwrap().as_mut() {
+            index.remove(from);
+            index.add(to);
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct FileIndexReady {
+    root: String,
+    file_count: usize,
+}
+
+#[derive(serde::Serialize)]
+pub struct FuzzyFileMatch {
+    path: String,
+    relative_path: String,
+    score: i64,
+    // Char positions in relative_path to highlight
+    indices: Vec<usize>,
+}
+
+/// Builds the file index for a workspace in the background and emits
+/// `file-index-ready` when done. Calling it again replaces the index.
+#[tauri::command]
+pub async fn index_workspace(
+    app: AppHandle,
+    state: State<'_, FileIndexState>,
+    path: String,
+    options: Option<WalkOptions>,
+) -> Result<(), String> {
+    let root = PathBuf::from(&path);
+    let watch_root = root.clone();
+    if !root.is_dir() {
+        return Err("Path is not a directory".to_string());
+    }
+
+    let generation = state.generation.fetch_add(1, Ordering::AcqRel) + 1;
+    let options = options.unwrap_or_default();
+
+    let index = tauri::async_runtime::spawn_blocking(move || FileIndex::build(&root, &options))
+        .await
+        .map_err(|e| format!("Failed to index workspace: {}", e))??;
+
+    // A newer request superseded this one while it was walking
+    if state.generation.load(Ordering::Acquire) != generation {
+        return Ok(());
+    }
+
+    let file_count = index.files.len();
+    *state.index.write().unwrap() = Some(index);
+
+    // Keeps the index current with changes outside the open tabs, and
+    // releases the watch on the previous workspace
+    if let Err(e) = app.state::<WatcherState>().set_index_root(&app, Some(watch_root)) {
+        eprintln!("Index for {} won't follow changes: {}", path, e);
+    }
+
+    let _ = app.emit("file-index-ready", FileIndexReady { root: path, file_count });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn clear_workspace_index(app: AppHandle, state: State<'_, FileIndexState>, watcher: State<'_, WatcherState>) {
+    state.generation.fetch_add(1, Ordering::AcqRel);
+    *state.index.write().unwrap() = None;
+    if let Err(e) = watcher.set_index_root(&app, None) {
+        eprintln!("Failed to release the workspace watch: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn fuzzy_find_files(app: AppHandle, query: String, limit: Option<usize>) -> Result<Vec<FuzzyFileMatch>, String> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    // Scoring a large workspace takes a while; keep it off the main thread
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<FileIndexState>();
+        let guard = state.index.read().unwrap();
+        let index = guard.as_ref().ok_or_else(|| "No workspace indexed".to_string())?;
+        Ok(index.find(query.trim(), limit))
+    })
+    .await
+    .map_err(|e| format!("File search failed: {}", e))?
+}
@@ -0,0 +1,778 @@
+use git2::{
+    Blob, Commit, Delta, DiffFindOptions, ErrorCode, IndexAddOption, Oid, Repository, RepositoryState, Sort,
+    Status, StatusOptions, Tree,
+};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::diff::{self, DiffOptions, DiffResult};
+use super::file::FileContent;
+use crate::encoding;
+
+/// What git thinks of a file. Ordered by precedence, so a directory shows
+/// the most important state among its contents.
+#[derive(serde::Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum GitFileState {
+    Ignored,
+    Untracked,
+    Added,
+    Renamed,
+    Deleted,
+    Modified,
+    Conflicted,
+}
+
+#[derive(serde::Serialize, Clone, Copy, PartialEq, Debug)]
+pub struct GitFileStatus {
+    state: GitFileState,
+    // Some of the change is in the index
+    staged: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct DirectoryStatus {
+    root: String,
+    branch: Option<String>,
+    // Keyed by the same absolute paths read_directory returns; clean entries are left out
+    entries: HashMap<String, GitFileStatus>,
+}
+
+#[derive(serde::Serialize, Debug)]
+pub struct GitChange {
+    path: String,
+    // Relative to the repository root, with forward slashes
+    relative_path: String,
+    state: GitFileState,
+    staged: bool,
+}
+
+#[derive(serde::Serialize, Debug)]
+pub struct GitCommitInfo {
+    id: String,
+    short_id: String,
+    summary: String,
+    message: String,
+    author: String,
+    email: String,
+    // Author time, milliseconds since the epoch
+    timestamp: i64,
+}
+
+#[derive(serde::Serialize, Debug)]
+pub struct BlameRange {
+    // One-based first line in the current file, and how many lines follow
+    start_line: usize,
+    lines: usize,
+    // None for lines that aren't committed yet
+    commit_id: Option<String>,
+    // Where the lines came from, when that was another file
+    original_path: Option<String>,
+    original_start_line: usize,
+}
+
+#[derive(serde::Serialize, Debug)]
+pub struct BlameResult {
+    ranges: Vec<BlameRange>,
+    // Every commit the ranges refer to, by id
+    commits: HashMap<String, GitCommitInfo>,
+}
+
+#[derive(serde::Serialize, Debug)]
+pub struct FileLogEntry {
+    #[serde(flatten)]
+    commit: GitCommitInfo,
+    // The file's path in this commit, relative to the repository root;
+    // differs from the current one before a rename
+    path: String,
+}
+
+const DEFAULT_LOG_LIMIT: usize = 200;
+
+fn git_error(e: git2::Error) -> String {
+    e.message().to_string()
+}
+
+fn short_id(id: Oid) -> String {
+    id.to_string()[..7].to_string()
+}
+
+/// The repository containing `path`, or None when it isn't inside a working tree.
+fn open_repo(path: &Path) -> Result<Option<Repository>, String> {
+    match Repository::discover(path) {
+        Ok(repo) if repo.workdir().is_some() => Ok(Some(repo)),
+        Ok(_) => Ok(None),
+        Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(git_error(e)),
+    }
+}
+
+fn require_repo(path: &Path) -> Result<Repository, String> {
+    open_repo(path)?.ok_or_else(|| "Not inside a git repository".to_string())
+}
+
+fn workdir(repo: &Repository) -> Result<PathBuf, String> {
+    let workdir = repo.workdir().ok_or_else(|| "Repository has no working tree".to_string())?;
+    fs::canonicalize(workdir).map_err(|e| format!("Failed to resolve repository: {}", e))
+}
+
+/// `path` relative to the working tree, as git spells it. Files that no longer
+/// exist are resolved through their parent directory.
+fn relative_path(workdir: &Path, path: &Path) -> Result<String, String> {
+    let resolved = match fs::canonicalize(path) {
+        Ok(resolved) => resolved,
+        Err(_) => {
+            let parent = path.parent().ok_or_else(|| "Invalid path".to_string())?;
+            let name = path.file_name().ok_or_else(|| "Invalid path".to_string())?;
+            fs::canonicalize(parent)
+                .map_err(|e| format!("Failed to resolve {}: {}", path.display(), e))?
+                .join(name)
+        }
+    };
+    let relative = resolved
+        .strip_prefix(workdir)
+        .map_err(|_| format!("{} is outside the repository", path.display()))?;
+    Ok(relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+fn classify(status: Status) -> Option<GitFileStatus> {
+    let staged = status.intersects(
+        Status::INDEX_NEW
+            | Status::INDEX_MODIFIED
+            | Status::INDEX_DELETED
+            | Status::INDEX_RENAMED
+            | Status::INDEX_TYPECHANGE,
+    );
+    let state = if status.is_conflicted() {
+        GitFileState::Conflicted
+    } else if status.is_ignored() {
+        GitFileState::Ignored
+    } else if status.is_wt_new() && !staged {
+        GitFileState::Untracked
+    } else if status.intersects(Status::INDEX_DELETED | Status::WT_DELETED) {
+        GitFileState::Deleted
+    } else if status.is_index_new() {
+        GitFileState::Added
+    } else if status.intersects(Status::INDEX_RENAMED | Status::WT_RENAMED) {
+        GitFileState::Renamed
+    } else if status.intersects(
+        Status::INDEX_MODIFIED | Status::WT_MODIFIED | Status::INDEX_TYPECHANGE | Status::WT_TYPECHANGE,
+    ) {
+        GitFileState::Modified
+    } else {
+        return None;
+    };
+    Some(GitFileStatus { state, staged })
+}
+
+/// Short name of the checked-out branch, or the abbreviated commit id when
+/// HEAD is detached. A fresh repository reports the branch it will create.
+fn current_branch(repo: &Repository) -> Option<String> {
+    match repo.head() {
+        Ok(head) if head.is_branch() => head.shorthand().map(String::from),
+        Ok(head) => head
+            .target()
+            .map(|id| format!("({})", short_id(id))),
+        Err(_) => repo
+            .find_reference("HEAD")
+            .ok()?
+            .symbolic_target()
+            .map(|target| target.trim_start_matches("refs/heads/").to_string()),
+    }
+}
+
+/// Status of each entry directly inside `dir`. Subdirectories take the
+/// highest-precedence state of anything changed beneath them; they only count
+/// as ignored when ignored themselves.
+pub(crate) fn directory_status(dir: &Path) -> Result<Option<DirectoryStatus>, String> {
+    let Some(repo) = open_repo(dir)? else { return Ok(None) };
+    let workdir = workdir(&repo)?;
+    let relative_dir = relative_path(&workdir, dir)?;
+    let prefix = if relative_dir.is_empty() { String::new() } else { format!("{}/", relative_dir) };
+
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .include_ignored(true)
+        .recurse_untracked_dirs(false)
+        .recurse_ignored_dirs(false)
+        .disable_pathspec_match(true);
+    if !relative_dir.is_empty() {
+        options.pathspec(&relative_dir);
+    }
+    let statuses = repo.statuses(Some(&mut options)).map_err(git_error)?;
+
+    let mut entries: HashMap<String, GitFileStatus> = HashMap::new();
+    for entry in statuses.iter() {
+        let Some(entry_path) = entry.path() else { continue };
+        let Some(rest) = entry_path.strip_prefix(&prefix) else { continue };
+        let Some(status) = classify(entry.status()) else { continue };
+
+        let (name, below) = match rest.split_once('/') {
+            Some((name, below)) => (name, below),
+            None => (rest, ""),
+        };
+        if name.is_empty() || (!below.is_empty() && status.state == GitFileState::Ignored) {
+            continue;
+        }
+
+        let key = dir.join(name).to_string_lossy().to_string();
+        entries
+            .entry(key)
+            .and_modify(|current| {
+                current.state = current.state.max(status.state);
+                current.staged |= status.staged;
+            })
+            .or_insert(status);
+    }
+
+    Ok(Some(DirectoryStatus {
+        root: workdir.to_string_lossy().to_string(),
+        branch: current_branch(&repo),
+        entries,
+    }))
+}
+
+/// Every changed or untracked file in the repository containing `path`.
+pub(crate) fn list_changes(path: &Path) -> Result<Vec<GitChange>, String> {
+    let Some(repo) = open_repo(path)? else { return Ok(Vec::new()) };
+    let workdir = workdir(&repo)?;
+
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(true);
+    let statuses = repo.statuses(Some(&mut options)).map_err(git_error)?;
+
+    let mut changes: Vec<GitChange> = statuses
+        .iter()
+        .filter_map(|entry| {
+            let relative_path = entry.path()?.to_string();
+            let status = classify(entry.status())?;
+            Some(GitChange {
+                path: workdir.join(&relative_path).to_string_lossy().to_string(),
+                relative_path,
+                state: status.state,
+                staged: status.staged,
+            })
+        })
+        .collect();
+    changes.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    Ok(changes)
+}
+
+fn entry_id(tree: &Tree, relative: &str) -> Result<Option<Oid>, String> {
+    match tree.get_path(Path::new(relative)) {
+        Ok(entry) => Ok(Some(entry.id())),
+        Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(git_error(e)),
+    }
+}
+
+fn blob_at<'repo>(repo: &'repo Repository, tree: &Tree, relative: &str) -> Result<Option<Blob<'repo>>, String> {
+    match entry_id(tree, relative)? {
+        Some(id) => repo.find_blob(id).map(Some).map_err(git_error),
+        None => Ok(None),
+    }
+}
+
+/// Content of `relative` in HEAD, empty when the file isn't committed yet.
+fn head_content(repo: &Repository, relative: &str) -> Result<String, String> {
+    let tree = match repo.head() {
+        Ok(head) => head.peel_to_tree().map_err(git_error)?,
+        Err(_) => return Ok(String::new()),
+    };
+    Ok(blob_at(repo, &tree, relative)?
+        .map(|blob| encoding::decode(blob.content()).content)
+        .unwrap_or_default())
+}
+
+pub(crate) fn diff_against_head(path: &Path, options: &DiffOptions) -> Result<DiffResult, String> {
+    let repo = require_repo(path)?;
+    let workdir = workdir(&repo)?;
+    let relative = relative_path(&workdir, path)?;
+
+    let old = head_content(&repo, &relative)?;
+    let new = match fs::read(path) {
+        Ok(bytes) => encoding::decode(&bytes).content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read file: {}", e)),
+    };
+
+    Ok(diff::compute(
+        &old,
+        &new,
+        options,
+        &format!("a/{}", relative),
+        &format!("b/{}", relative),
+    ))
+}
+
+/// Stages the given files; deleted files are removed from the index.
+pub(crate) fn stage_files(repo: &Repository, files: &[PathBuf]) -> Result<(), String> {
+    let workdir = workdir(repo)?;
+    let mut index = repo.index().map_err(git_error)?;
+    for file in files {
+        let relative = relative_path(&workdir, file)?;
+        if file.is_dir() {
+            index
+                .add_all([relative.as_str()], IndexAddOption::DEFAULT, None)
+                .map_err(git_error)?;
+        } else if file.exists() {
+            index.add_path(Path::new(&relative)).map_err(git_error)?;
+        } else {
+            index.remove_path(Path::new(&relative)).map_err(git_error)?;
+        }
+    }
+    index.write().map_err(git_error)
+}
+
+/// Stages `files` and commits the index on top of HEAD. Anything staged
+/// earlier is committed too, as with `git commit`. Returns the new commit id.
+pub(crate) fn commit_files(path: &Path, files: &[PathBuf], message: &str) -> Result<String, String> {
+    if message.trim().is_empty() {
+        return Err("Commit message is empty".to_string());
+    }
+    let repo = require_repo(path)?;
+    if repo.state() != RepositoryState::Clean {
+        return Err("Finish the merge or rebase in progress before committing".to_string());
+    }
+
+    stage_files(&repo, files)?;
+
+    let mut index = repo.index().map_err(git_error)?;
+    let tree = index
+        .write_tree()
+        .and_then(|id| repo.find_tree(id))
+        .map_err(git_error)?;
+    let parent = match repo.head() {
+        Ok(head) => Some(head.peel_to_commit().map_err(git_error)?),
+        Err(_) => None,
+    };
+    if parent.as_ref().is_some_and(|parent| parent.tree_id() == tree.id()) {
+        return Err("Nothing to commit".to_string());
+    }
+
+    let signature = repo
+        .signature()
+        .map_err(|_| "Set user.name and user.email in your git config to commit".to_string())?;
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let id = repo
+        .commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)
+        .map_err(git_error)?;
+    Ok(id.to_string())
+}
+
+fn commit_info(commit: &Commit) -> GitCommitInfo {
+    let author = commit.author();
+    GitCommitInfo {
+        id: commit.id().to_string(),
+        short_id: short_id(commit.id()),
+        summary: commit.summary().unwrap_or_default().to_string(),
+        message: commit.message().unwrap_or_default().to_string(),
+        author: author.name().unwrap_or_default().to_string(),
+        email: author.email().unwrap_or_default().to_string(),
+        timestamp: author.when().seconds() * 1000,
+    }
+}
+
+/// Line ranges of the file on disk with the commit that last changed each.
+/// Uncommitted edits come back without a commit.
+pub(crate) fn blame_file(path: &Path) -> Result<BlameResult, String> {
+    let repo = require_repo(path)?;
+    let relative = relative_path(&workdir(&repo)?, path)?;
+
+    let committed = repo.blame_file(Path::new(&relative), None).map_err(|e| match e.code() {
+        ErrorCode::NotFound | ErrorCode::UnbornBranch => format!("{} has no committed history", relative),
+        _ => git_error(e),
+    })?;
+    let blame = match fs::read(path) {
+        Ok(bytes) => committed.blame_buffer(&bytes).map_err(git_error)?,
+        // Deleted since the last commit: blame what was committed
+        Err(_) => committed,
+    };
+
+    let mut ranges = Vec::with_capacity(blame.len());
+    let mut commits = HashMap::new();
+    for hunk in blame.iter() {
+        let id = hunk.final_commit_id();
+        let commit_id = if id.is_zero() {
+            None
+        } else {
+            let key = id.to_string();
+            if !commits.contains_key(&key) {
+                let commit = repo.find_commit(id).map_err(git_error)?;
+                commits.insert(key.clone(), commit_info(&commit));
+            }
+            Some(key)
+        };
+        let original_path = hunk
+            .path()
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .filter(|p| *p != relative);
+
+        ranges.push(BlameRange {
+            start_line: hunk.final_start_line(),
+            lines: hunk.lines_in_hunk(),
+            commit_id,
+            original_path,
+            original_start_line: hunk.orig_start_line(),
+        });
+    }
+
+    Ok(BlameResult { ranges, commits })
+}
+
+// The path `relative` had in `old_tree`, if the change to `new_tree` renamed it
+fn renamed_from(repo: &Repository, old_tree: &Tree, new_tree: &Tree, relative: &str) -> Result<Option<String>, String> {
+    let mut diff = repo
+        .diff_tree_to_tree(Some(old_tree), Some(new_tree), None)
+        .map_err(git_error)?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))
+        .map_err(git_error)?;
+    Ok(diff
+        .deltas()
+        .find(|delta| delta.status() == Delta::Renamed && delta.new_file().path_bytes() == Some(relative.as_bytes()))
+        .and_then(|delta| delta.old_file().path_bytes())
+        .map(|old| String::from_utf8_lossy(old).to_string()))
+}
+
+/// Commits reachable from HEAD that changed the file, newest first, following
+/// it back through renames.
+pub(crate) fn file_log(path: &Path, limit: usize) -> Result<Vec<FileLogEntry>, String> {
+    let repo = require_repo(path)?;
+    let mut relative = relative_path(&workdir(&repo)?, path)?;
+
+    let mut revwalk = repo.revwalk().map_err(git_error)?;
+    match revwalk.push_head() {
+        Ok(()) => {}
+        Err(e) if matches!(e.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => return Ok(Vec::new()),
+        Err(e) => return Err(git_error(e)),
+    }
+    revwalk
+        .set_sorting(Sort::TOPOLOGICAL | Sort::TIME)
+        .map_err(git_error)?;
+
+    let mut entries = Vec::new();
+    for id in revwalk {
+        if entries.len() >= limit {
+            break;
+        }
+        let commit = repo.find_commit(id.map_err(git_error)?).map_err(git_error)?;
+        let tree = commit.tree().map_err(git_error)?;
+        let Some(blob_id) = entry_id(&tree, &relative)? else { continue };
+
+        let parent_trees = commit
+            .parents()
+            .map(|parent| parent.tree())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(git_error)?;
+        // As with git log, a merge that kept one parent's version didn't change the file
+        let mut unchanged = false;
+        for parent_tree in &parent_trees {
+            if entry_id(parent_tree, &relative)? == Some(blob_id) {
+                unchanged = true;
+                break;
+            }
+        }
+        if unchanged {
+            continue;
+        }
+
+        entries.push(FileLogEntry {
+            commit: commit_info(&commit),
+            path: relative.clone(),
+        });
+
+        // Where the file first appears under this name, keep following its old one
+        if let Some(parent_tree) = parent_trees.first() {
+            if entry_id(parent_tree, &relative)?.is_none() {
+                if let Some(old) = renamed_from(&repo, parent_tree, &tree, &relative)? {
+                    relative = old;
+                }
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// The file as it was in `revision` (a commit id, branch or expression such as
+/// `HEAD~2`). `path_at_revision` names the file there when it has been renamed
+/// since, as reported by the file log.
+pub(crate) fn file_at_revision(
+    path: &Path,
+    revision: &str,
+    path_at_revision: Option<&str>,
+) -> Result<encoding::DecodedText, String> {
+    let repo = require_repo(path)?;
+    let relative = match path_at_revision {
+        Some(relative) => relative.to_string(),
+        None => relative_path(&workdir(&repo)?, path)?,
+    };
+    let commit = repo
+        .revparse_single(revision)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|_| format!("Unknown revision {}", revision))?;
+    let tree = commit.tree().map_err(git_error)?;
+    let blob = blob_at(&repo, &tree, &relative)?
+        .ok_or_else(|| format!("{} does not exist in {}", relative, short_id(commit.id())))?;
+    Ok(encoding::decode(blob.content()))
+}
+
+/// Per-entry git status for a directory listing, or None outside a repository.
+#[tauri::command]
+pub async fn git_status(path: String) -> Result<Option<DirectoryStatus>, String> {
+    tauri::async_runtime::spawn_blocking(move || directory_status(Path::new(&path)))
+        .await
+        .map_err(|e| format!("Git status failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn git_changes(path: String) -> Result<Vec<GitChange>, String> {
+    tauri::async_runtime::spawn_blocking(move || list_changes(Path::new(&path)))
+        .await
+        .map_err(|e| format!("Git status failed: {}", e))?
+}
+
+#[tauri::command]
+pub fn git_branch(path: String) -> Result<Option<String>, String> {
+    Ok(open_repo(Path::new(&path))?.and_then(|repo| current_branch(&repo)))
+}
+
+/// Diff of the working file against its committed version.
+#[tauri::command]
+pub async fn git_diff_file(path: String, options: Option<DiffOptions>) -> Result<DiffResult, String> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || diff_against_head(Path::new(&path), &options))
+        .await
+        .map_err(|e| format!("Git diff failed: {}", e))?
+}
+
+#[tauri::command]
+pub fn git_stage(files: Vec<String>) -> Result<(), String> {
+    let Some(first) = files.first() else { return Ok(()) };
+    let repo = require_repo(Path::new(first))?;
+    let files: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+    stage_files(&repo, &files)
+}
+
+#[tauri::command]
+pub fn git_commit(path: String, files: Vec<String>, message: String) -> Result<String, String> {
+    let files: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+    commit_files(Path::new(&path), &files, &message)
+}
+
+/// Who last changed each part of a file, for reviewing shared files.
+#[tauri::command]
+pub async fn git_blame(path: String) -> Result<BlameResult, String> {
+    tauri::async_runtime::spawn_blocking(move || blame_file(Path::new(&path)))
+        .await
+        .map_err(|e| format!("Git blame failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn git_file_log(path: String, limit: Option<usize>) -> Result<Vec<FileLogEntry>, String> {
+    let limit = limit.unwrap_or(DEFAULT_LOG_LIMIT);
+    tauri::async_runtime::spawn_blocking(move || file_log(Path::new(&path), limit))
+        .await
+        .map_err(|e| format!("Git log failed: {}", e))?
+}
+
+/// Content of a file at an earlier commit, decoded like read_file.
+#[tauri::command]
+pub fn git_file_at_revision(
+    path: String,
+    revision: String,
+    path_at_revision: Option<String>,
+) -> Result<FileContent, String> {
+    file_at_revision(Path::new(&path), &revision, path_at_revision.as_deref()).map(FileContent::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::RepositoryInitOptions;
+    use tempfile::TempDir;
+
+    fn init_repo() -> (TempDir, Repository) {
+        let dir = tempfile::tempdir().unwrap();
+        let mut options = RepositoryInitOptions::new();
+        options.initial_head("main");
+        let repo = Repository::init_opts(dir.path(), &options).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        (dir, repo)
+    }
+
+    fn write(dir: &Path, relative: &str, content: &str) -> PathBuf {
+        let path = dir.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn state_of(status: &DirectoryStatus, path: &Path) -> Option<GitFileState> {
+        status
+            .entries
+            .get(&path.to_string_lossy().to_string())
+            .map(|s| s.state)
+    }
+
+    #[test]
+    fn outside_repository_has_no_status() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(directory_status(dir.path()).unwrap().is_none());
+        assert!(list_changes(dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reports_branch_of_unborn_repository() {
+        let (dir, _repo) = init_repo();
+        let status = directory_status(dir.path()).unwrap().unwrap();
+        assert_eq!(status.branch.as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn classifies_directory_entries() {
+        let (dir, _repo) = init_repo();
+        let root = dir.path();
+        let tracked = write(root, "tracked.md", "one\n");
+        let nested = write(root, "docs/guide.md", "guide\n");
+        write(root, ".gitignore", "build/\n*.log\n");
+        commit_files(root, &[tracked.clone(), nested, root.join(".gitignore")], "initial").unwrap();
+
+        write(root, "tracked.md", "two\n");
+        write(root, "docs/guide.md", "changed\n");
+        let untracked = write(root, "notes.txt", "new\n");
+        write(root, "build/out.bin", "x");
+        write(root, "docs/debug.log", "noise");
+
+        let status = directory_status(root).unwrap().unwrap();
+        assert_eq!(state_of(&status, &tracked), Some(GitFileState::Modified));
+        assert_eq!(state_of(&status, &untracked), Some(GitFileState::Untracked));
+        assert_eq!(state_of(&status, &root.join("build")), Some(GitFileState::Ignored));
+        // A changed file below wins over an ignored one
+        assert_eq!(state_of(&status, &root.join("docs")), Some(GitFileState::Modified));
+        assert_eq!(state_of(&status, &root.join(".gitignore")), None);
+
+        let docs = directory_status(&root.join("docs")).unwrap().unwrap();
+        assert_eq!(state_of(&docs, &root.join("docs/guide.md")), Some(GitFileState::Modified));
+        assert_eq!(state_of(&docs, &root.join("docs/debug.log")), Some(GitFileState::Ignored));
+        assert_eq!(docs.entries.len(), 2);
+    }
+
+    #[test]
+    fn diffs_file_against_head() {
+        let (dir, _repo) = init_repo();
+        let file = write(dir.path(), "prompt.md", "alpha\nbeta\ngamma\n");
+        commit_files(dir.path(), std::slice::from_ref(&file), "add prompt").unwrap();
+        write(dir.path(), "prompt.md", "alpha\nBETA\ngamma\n");
+
+        let options = DiffOptions::default();
+        let result = diff_against_head(&file, &options).unwrap();
+        assert_eq!((result.insertions, result.deletions), (1, 1));
+
+        let untracked = write(dir.path(), "new.md", "fresh\n");
+        let result = diff_against_head(&untracked, &options).unwrap();
+        assert_eq!((result.insertions, result.deletions), (1, 0));
+    }
+
+    #[test]
+    fn commits_selected_files_only() {
+        let (dir, repo) = init_repo();
+        let first = write(dir.path(), "a.md", "a\n");
+        let second = write(dir.path(), "b.md", "b\n");
+
+        let id = commit_files(dir.path(), std::slice::from_ref(&first), "add a").unwrap();
+        let commit = repo.find_commit(git2::Oid::from_str(&id).unwrap()).unwrap();
+        assert_eq!(commit.message(), Some("add a"));
+        assert_eq!(commit.parent_count(), 0);
+        assert!(commit.tree().unwrap().get_path(Path::new("a.md")).is_ok());
+        assert!(commit.tree().unwrap().get_path(Path::new("b.md")).is_err());
+
+        let changes = list_changes(dir.path()).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].relative_path, "b.md");
+        assert_eq!(changes[0].state, GitFileState::Untracked);
+
+        fs::remove_file(&first).unwrap();
+        let id = commit_files(dir.path(), &[first, second], "swap").unwrap();
+        let commit = repo.find_commit(git2::Oid::from_str(&id).unwrap()).unwrap();
+        assert_eq!(commit.parent_count(), 1);
+        assert!(commit.tree().unwrap().get_path(Path::new("a.md")).is_err());
+        assert!(list_changes(dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_empty_commits() {
+        let (dir, _repo) = init_repo();
+        let file = write(dir.path(), "a.md", "a\n");
+        assert!(commit_files(dir.path(), std::slice::from_ref(&file), "  ").is_err());
+        commit_files(dir.path(), std::slice::from_ref(&file), "add a").unwrap();
+        assert_eq!(commit_files(dir.path(), &[file], "again").unwrap_err(), "Nothing to commit");
+    }
+
+    #[test]
+    fn blames_committed_and_uncommitted_lines() {
+        let (dir, repo) = init_repo();
+        let file = write(dir.path(), "prompt.md", "intro\nbody\n");
+        let first = commit_files(dir.path(), std::slice::from_ref(&file), "add prompt").unwrap();
+
+        repo.config().unwrap().set_str("user.name", "Other").unwrap();
+        write(dir.path(), "prompt.md", "intro\nbody changed\noutro\n");
+        let second = commit_files(dir.path(), std::slice::from_ref(&file), "edit prompt").unwrap();
+        write(dir.path(), "prompt.md", "intro edited\nbody changed\noutro\n");
+
+        let blame = blame_file(&file).unwrap();
+        let lines: Vec<(usize, usize, Option<&str>)> = blame
+            .ranges
+            .iter()
+            .map(|range| (range.start_line, range.lines, range.commit_id.as_deref()))
+            .collect();
+        assert_eq!(lines, vec![(1, 1, None), (2, 2, Some(second.as_str()))]);
+        assert_eq!(blame.commits[&second].author, "Other");
+        assert!(!blame.commits.contains_key(&first));
+
+        let untracked = write(dir.path(), "new.md", "x\n");
+        assert!(blame_file(&untracked).is_err());
+    }
+
+    #[test]
+    fn file_log_follows_renames() {
+        let (dir, _repo) = init_repo();
+        let old = write(dir.path(), "old.md", "one\ntwo\nthree\n");
+        let other = write(dir.path(), "other.md", "unrelated\n");
+        commit_files(dir.path(), &[old.clone(), other.clone()], "add").unwrap();
+        write(dir.path(), "other.md", "still unrelated\n");
+        commit_files(dir.path(), std::slice::from_ref(&other), "touch other").unwrap();
+        write(dir.path(), "old.md", "one\ntwo\nthree\nfour\n");
+        commit_files(dir.path(), std::slice::from_ref(&old), "extend").unwrap();
+
+        fs::rename(&old, dir.path().join("new.md")).unwrap();
+        let new = dir.path().join("new.md");
+        commit_files(dir.path(), &[old, new.clone()], "rename").unwrap();
+
+        let log = file_log(&new, 10).unwrap();
+        let summary: Vec<(&str, &str)> = log
+            .iter()
+            .map(|entry| (entry.commit.summary.as_str(), entry.path.as_str()))
+            .collect();
+        assert_eq!(summary, vec![("rename", "new.md"), ("extend", "old.md"), ("add", "old.md")]);
+        assert_eq!(file_log(&new, 1).unwrap().len(), 1);
+
+        let previous = file_at_revision(&new, "HEAD~1", Some("old.md")).unwrap();
+        assert_eq!(previous.content, "one\ntwo\nthree\nfour\n");
+        let first = file_at_revision(&new, &log[2].commit.id, Some(&log[2].path)).unwrap();
+        assert_eq!(first.content, "one\ntwo\nthree\n");
+        assert!(file_at_revision(&new, "HEAD~1", None).is_err());
+        assert!(file_at_revision(&new, "no-such-branch", None).is_err());
+    }
+}
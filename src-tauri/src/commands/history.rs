@@ -0,0 +1,338 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+use super::diff::{diff_lines, DiffLine};
+use super::file::{atomic_write, encode_content, hash_bytes, FileContent};
+use super::watcher::WatcherState;
+use crate::encoding::{self, LineEnding};
+
+// Snapshots live in <app data>/history:
+//   files/<sha256 of path>.json   revision list for one file
+//   objects/<ab>/<cdef…>.gz       gzipped content, shared by every revision with that hash
+
+const DEFAULT_MAX_REVISIONS: usize = 50;
+const DEFAULT_MAX_AGE_DAYS: u64 = 30;
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotSource {
+    Save,
+    Autosave,
+    // Content on disk just before a revision was restored over it
+    Restore,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Revision {
+    id: u64,
+    // Milliseconds since the Unix epoch
+    timestamp: u64,
+    hash: String,
+    size: u64,
+    source: SnapshotSource,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct FileHistory {
+    path: String,
+    revisions: Vec<Revision>,
+}
+
+/// Retention rules, sent with each write like the backup options.
+#[derive(Clone, serde::Deserialize)]
+pub struct HistoryOptions {
+    max_revisions: Option<usize>,
+    // 0 keeps revisions regardless of age
+    max_age_days: Option<u64>,
+}
+
+pub struct HistoryState {
+    root: PathBuf,
+    // Serialises index updates so concurrent saves don't drop revisions
+    lock: Mutex<()>,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl HistoryState {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn index_dir(&self) -> PathBuf {
+        self.root.join("files")
+    }
+
+    fn index_path(&self, path: &Path) -> PathBuf {
+        let key = hash_bytes(path.to_string_lossy().as_bytes());
+        self.index_dir().join(format!("{}.json", key))
+    }
+
+    fn object_path(&self, hash: &str) -> PathBuf {
+        let (prefix, rest) = hash.split_at(2);
+        self.root.join("objects").join(prefix).join(format!("{}.gz", rest))
+    }
+
+    fn load(&self, path: &Path) -> Result<FileHistory, String> {
+        match fs::read(self.index_path(path)) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| format!("Corrupt history index: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(FileHistory {
+                path: path.to_string_lossy().to_string(),
+                revisions: Vec::new(),
+            }),
+            Err(e) => Err(format!("Failed to read history: {}", e)),
+        }
+    }
+
+    fn store(&self, path: &Path, history: &FileHistory) -> Result<(), String> {
+        let index_path = self.index_path(path);
+        if history.revisions.is_empty() {
+            let _ = fs::remove_file(&index_path);
+            return Ok(());
+        }
+
+        fs::create_dir_all(self.index_dir())
+            .map_err(|e| format!("Failed to create history directory: {}", e))?;
+        let json = serde_json::to_vec(history)
+            .map_err(|e| format!("Failed to serialize history: {}", e))?;
+        atomic_write(&index_path, &json)
+            .map_err(|e| format!("Failed to write history: {}", e))
+    }
+
+    fn write_object(&self, hash: &str, bytes: &[u8]) -> Result<(), String> {
+        let object_path = self.object_path(hash);
+        if object_path.exists() {
+            return Ok(());
+        }
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes)
+            .and_then(|_| encoder.finish())
+            .and_then(|compressed| {
+                fs::create_dir_all(object_path.parent().unwrap())?;
+                atomic_write(&object_path, &compressed)
+            })
+            .map_err(|e| format!("Failed to store snapshot: {}", e))
+    }
+
+    fn read_object(&self, hash: &str) -> Result<Vec<u8>, String> {
+        let file = fs::File::open(self.object_path(hash))
+            .map_err(|e| format!("Failed to open snapshot: {}", e))?;
+        let mut bytes = Vec::new();
+        GzDecoder::new(file)
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("Failed to read snapshot: {}", e))?;
+        Ok(bytes)
+    }
+
+    // Objects are shared across files, so one is only deleted once no index references it
+    fn collect_garbage(&self, candidates: HashSet<String>) {
+        let mut unreferenced = candidates;
+        let Ok(entries) = fs::read_dir(self.index_dir()) else { return };
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            if unreferenced.is_empty() {
+                return;
+            }
+            let Ok(bytes) = fs::read(entry.path()) else { continue };
+            let Ok(history) = serde_json::from_slice::<FileHistory>(&bytes) else { continue };
+            for revision in &history.revisions {
+                unreferenced.remove(&revision.hash);
+            }
+        }
+
+        for hash in unreferenced {
+            let _ = fs::remove_file(self.object_path(&hash));
+        }
+    }
+
+    /// Records `bytes` as the newest revision of `path`, unless it matches the
+    /// current newest one. Returns the new revision, if any.
+    pub fn record(
+        &self,
+        path: &Path,
+        bytes: &[u8],
+        source: SnapshotSource,
+        options: &HistoryOptions,
+    ) -> Result<Option<Revision>, String> {
+        let _guard = self.lock.lock().unwrap();
+        let mut history = self.load(path)?;
+        let hash = hash_bytes(bytes);
+
+        if history.revisions.last().is_some_and(|last| last.hash == hash) {
+            return Ok(None);
+        }
+
+        self.write_object(&hash, bytes)?;
+
+        let timestamp = now_millis();
+        // Ids are timestamps, bumped when two snapshots land in the same millisecond
+        let id = history
+            .revisions
+            .last()
+            .map_or(timestamp, |last| timestamp.max(last.id + 1));
+        let revision = Revision {
+            id,
+            timestamp,
+            hash,
+            size: bytes.len() as u64,
+            source,
+        };
+        history.revisions.push(revision.clone());
+
+        let removed = prune(&mut history.revisions, options, timestamp);
+        self.store(path, &history)?;
+
+        let live: HashSet<&str> = history.revisions.iter().map(|r| r.hash.as_str()).collect();
+        let candidates: HashSet<String> = removed
+            .into_iter()
+            .filter(|r| !live.contains(r.hash.as_str()))
+            .map(|r| r.hash)
+            .collect();
+        if !candidates.is_empty() {
+            self.collect_garbage(candidates);
+        }
+
+        Ok(Some(revision))
+    }
+
+    fn revision_bytes(&self, path: &Path, id: u64) -> Result<Vec<u8>, String> {
+        let history = self.load(path)?;
+        let revision = history
+            .revisions
+            .iter()
+            .find(|r| r.id == id)
+            .ok_or_else(|| "Revision not found".to_string())?;
+        self.read_object(&revision.hash)
+    }
+
+    /// Content of the newest revision written by a save, if any.
+    pub(crate) fn latest_saved(&self, path: &Path) -> Result<Option<Vec<u8>>, String> {
+        let history = self.load(path)?;
+        match history.revisions.iter().rev().find(|r| r.source == SnapshotSource::Save) {
+            Some(revision) => self.read_object(&revision.hash).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+// Drops revisions over the count limit or past the age limit, always keeping the newest
+fn prune(revisions: &mut Vec<Revision>, options: &HistoryOptions, now: u64) -> Vec<Revision> {
+    let max_revisions = options.max_revisions.unwrap_or(DEFAULT_MAX_REVISIONS).max(1);
+    let max_age_days = options.max_age_days.unwrap_or(DEFAULT_MAX_AGE_DAYS);
+    let cutoff = match max_age_days {
+        0 => 0,
+        days => now.saturating_sub(days * 24 * 60 * 60 * 1000),
+    };
+
+    let newest = revisions.len().saturating_sub(1);
+    let first_kept = revisions.len().saturating_sub(max_revisions);
+    let mut removed = Vec::new();
+    let mut index = 0;
+
+    revisions.retain(|revision| {
+        let keep = index == newest || (index >= first_kept && revision.timestamp >= cutoff);
+        if !keep {
+            removed.push(revision.clone());
+        }
+        index += 1;
+        keep
+    });
+
+    removed
+}
+
+/// Records the unsaved buffer of a file, encoded the way write_file would
+/// save it. Called by the frontend on its autosave interval.
+#[tauri::command]
+pub async fn record_snapshot(
+    snapshots: State<'_, HistoryState>,
+    path: String,
+    content: String,
+    encoding: Option<String>,
+    has_bom: Option<bool>,
+    line_ending: Option<LineEnding>,
+    options: HistoryOptions,
+) -> Result<Option<Revision>, String> {
+    let bytes = encode_content(&content, encoding.as_deref(), has_bom, line_ending)?;
+    snapshots.record(Path::new(&path), &bytes, SnapshotSource::Autosave, &options)
+}
+
+/// Lists the revisions of a file, newest first.
+#[tauri::command]
+pub fn list_revisions(snapshots: State<'_, HistoryState>, path: String) -> Result<Vec<Revision>, String> {
+    let mut revisions = snapshots.load(Path::new(&path))?.revisions;
+    revisions.reverse();
+    Ok(revisions)
+}
+
+#[tauri::command]
+pub fn read_revision(snapshots: State<'_, HistoryState>, path: String, id: u64) -> Result<FileContent, String> {
+    let bytes = snapshots.revision_bytes(Path::new(&path), id)?;
+    Ok(encoding::decode(&bytes).into())
+}
+
+/// Line diff between two revisions. Without `to`, compares against the file
+/// as it is on disk now.
+#[tauri::command]
+pub async fn diff_revisions(
+    snapshots: State<'_, HistoryState>,
+    path: String,
+    from: u64,
+    to: Option<u64>,
+) -> Result<Vec<DiffLine>, String> {
+    let path_buf = PathBuf::from(&path);
+    let old = snapshots.revision_bytes(&path_buf, from)?;
+    let new = match to {
+        Some(id) => snapshots.revision_bytes(&path_buf, id)?,
+        None => fs::read(&path_buf).map_err(|e| format!("Failed to read file: {}", e))?,
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        diff_lines(&encoding::decode(&old).content, &encoding::decode(&new).content)
+    })
+    .await
+    .map_err(|e| format!("Diff failed: {}", e))
+}
+
+/// Writes a revision back to the file. The content it replaces is recorded
+/// first, so a restore can itself be undone.
+#[tauri::command]
+pub async fn restore_revision(
+    watcher: State<'_, WatcherState>,
+    snapshots: State<'_, HistoryState>,
+    path: String,
+    id: u64,
+    options: HistoryOptions,
+) -> Result<FileContent, String> {
+    let path_buf = PathBuf::from(&path);
+    let bytes = snapshots.revision_bytes(&path_buf, id)?;
+
+    if let Ok(current) = fs::read(&path_buf) {
+        snapshots.record(&path_buf, &current, SnapshotSource::Restore, &options)?;
+    }
+
+    atomic_write(&path_buf, &bytes)
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+    watcher.record_hash(&path_buf, hash_bytes(&bytes));
+    snapshots.record(&path_buf, &bytes, SnapshotSource::Save, &options)?;
+
+    Ok(encoding::decode(&bytes).into())
+}
@@ -0,0 +1,265 @@
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+use super::file::atomic_write;
+use crate::encoding;
+
+// Each tab with unsaved changes gets <app data>/journal/<tab id>.jsonl: a
+// header line, a checkpoint with the full buffer, then one line per batch of
+// edits. Replaying the lines rebuilds the buffer without the webview's storage.
+
+// Past this size the journal is replayed and rewritten as a single checkpoint
+const COMPACT_THRESHOLD: u64 = 1024 * 1024;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct JournalHeader {
+    tab_id: String,
+    path: Option<String>,
+    title: String,
+}
+
+/// One edit in the coordinates the editor uses: UTF-16 code unit offsets
+/// into the buffer as it was before the edit.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct JournalChange {
+    from: usize,
+    to: usize,
+    insert: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum JournalEntry {
+    Checkpoint { content: String },
+    Changes { changes: Vec<JournalChange> },
+}
+
+pub struct JournalState {
+    root: PathBuf,
+    lock: Mutex<()>,
+}
+
+fn millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn utf16_to_byte(text: &str, offset: usize) -> Option<usize> {
+    let mut units = 0;
+    for (index, c) in text.char_indices() {
+        if units == offset {
+            return Some(index);
+        }
+        units += c.len_utf16();
+    }
+    (units == offset).then_some(text.len())
+}
+
+fn apply_change(content: &mut String, change: &JournalChange) -> Result<(), String> {
+    let from = utf16_to_byte(content, change.from);
+    let to = utf16_to_byte(content, change.to);
+    match (from, to) {
+        (Some(from), Some(to)) if from <= to => {
+            content.replace_range(from..to, &change.insert);
+            Ok(())
+        }
+        _ => Err("Journal change out of range".to_string()),
+    }
+}
+
+impl JournalState {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn journal_path(&self, tab_id: &str) -> Result<PathBuf, String> {
+        // Tab ids are UUIDs; anything else could escape the journal directory
+        if tab_id.is_empty() || !tab_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err("Invalid tab id".to_string());
+        }
+        Ok(self.root.join(format!("{}.jsonl", tab_id)))
+    }
+
+    fn write_checkpoint(&self, journal_path: &Path, header: &JournalHeader, content: String) -> Result<(), String> {
+        let mut text = serde_json::to_string(header)
+            .map_err(|e| format!("Failed to serialize journal: {}", e))?;
+        text.push('\n');
+        text.push_str(
+            &serde_json::to_string(&JournalEntry::Checkpoint { content })
+                .map_err(|e| format!("Failed to serialize journal: {}", e))?,
+        );
+        text.push('\n');
+
+        fs::create_dir_all(&self.root)
+            .map_err(|e| format!("Failed to create journal directory: {}", e))?;
+        atomic_write(journal_path, text.as_bytes())
+            .map_err(|e| format!("Failed to write journal: {}", e))
+    }
+}
+
+// A torn final line (the app died mid-append) is ignored, as are changes
+// that no longer apply; the buffer is rebuilt up to the last good entry
+fn replay(journal_path: &Path) -> Result<(JournalHeader, String), String> {
+    let file = fs::File::open(journal_path)
+        .map_err(|e| format!("Failed to open journal: {}", e))?;
+    let mut lines = BufReader::new(file).lines();
+
+    let header: JournalHeader = lines
+        .next()
+        .and_then(|line| line.ok())
+        .and_then(|line| serde_json::from_str(&line).ok())
+        .ok_or_else(|| "Journal has no header".to_string())?;
+
+    let mut content = String::new();
+    for line in lines {
+        let Ok(line) = line else { break };
+        let Ok(entry) = serde_json::from_str::<JournalEntry>(&line) else { break };
+        match entry {
+            JournalEntry::Checkpoint { content: checkpoint } => content = checkpoint,
+            JournalEntry::Changes { changes } => {
+                let mut next = content.clone();
+                if changes.iter().try_for_each(|change| apply_change(&mut next, change)).is_err() {
+                    break;
+                }
+                content = next;
+            }
+        }
+    }
+
+    Ok((header, content))
+}
+
+/// Starts (or restarts) the journal for a tab from its full content.
+#[tauri::command]
+pub fn journal_checkpoint(
+    journal: State<'_, JournalState>,
+    tab_id: String,
+    path: Option<String>,
+    title: String,
+    content: String,
+) -> Result<(), String> {
+    let journal_path = journal.journal_path(&tab_id)?;
+    let _guard = journal.lock.lock().unwrap();
+    let header = JournalHeader { tab_id, path, title };
+    journal.write_checkpoint(&journal_path, &header, content)
+}
+
+/// Appends a batch of edits. Fails when the tab has no journal yet, in which
+/// case the frontend should send a checkpoint instead.
+#[tauri::command]
+pub fn journal_append(
+    journal: State<'_, JournalState>,
+    tab_id: String,
+    changes: Vec<JournalChange>,
+) -> Result<(), String> {
+    let journal_path = journal.journal_path(&tab_id)?;
+    let _guard = journal.lock.lock().unwrap();
+
+    let mut line = serde_json::to_string(&JournalEntry::Changes { changes })
+        .map_err(|e| format!("Failed to serialize journal: {}", e))?;
+    line.push('\n');
+
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(&journal_path)
+        .map_err(|e| format!("No journal for tab: {}", e))?;
+    file.write_all(line.as_bytes())
+        .and_then(|_| file.sync_data())
+        .map_err(|e| format!("Failed to append to journal: {}", e))?;
+
+    let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+    drop(file);
+    if size > COMPACT_THRESHOLD {
+        let (header, content) = replay(&journal_path)?;
+        journal.write_checkpoint(&journal_path, &header, content)?;
+    }
+
+    Ok(())
+}
+
+/// Drops a tab's journal once its changes are saved or deliberately discarded.
+#[tauri::command]
+pub fn journal_discard(journal: State<'_, JournalState>, tab_id: String) -> Result<(), String> {
+    let journal_path = journal.journal_path(&tab_id)?;
+    let _guard = journal.lock.lock().unwrap();
+    match fs::remove_file(&journal_path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove journal: {}", e)),
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct RecoverableBuffer {
+    tab_id: String,
+    path: Option<String>,
+    title: String,
+    content: String,
+    journal_modified: u64,
+    // None for untitled buffers and files that no longer exist
+    file_modified: Option<u64>,
+    // The file changed on disk after the journal was last written, so
+    // recovering would replace those changes
+    conflict: bool,
+}
+
+/// Journals whose buffer differs from the file, which means the edits never
+/// reached disk. Only journals that match their file are removed; one older
+/// than its file is still offered, flagged as a conflict.
+#[tauri::command]
+pub fn list_recoverable_buffers(journal: State<'_, JournalState>) -> Result<Vec<RecoverableBuffer>, String> {
+    let _guard = journal.lock.lock().unwrap();
+    let Ok(entries) = fs::read_dir(&journal.root) else { return Ok(Vec::new()) };
+
+    let mut recoverable = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let journal_path = entry.path();
+        if journal_path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+            continue;
+        }
+
+        let journal_modified = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .map(millis)
+            .unwrap_or(0);
+        let Ok((header, content)) = replay(&journal_path) else {
+            let _ = fs::remove_file(&journal_path);
+            continue;
+        };
+
+        let file_modified = header.path.as_ref().and_then(|path| {
+            fs::metadata(path).and_then(|m| m.modified()).map(millis).ok()
+        });
+
+        let stale = match (&header.path, file_modified) {
+            (Some(path), Some(_)) => fs::read(path).is_ok_and(|bytes| encoding::decode(&bytes).content == content),
+            _ => content.is_empty(),
+        };
+        if stale {
+            let _ = fs::remove_file(&journal_path);
+            continue;
+        }
+
+        recoverable.push(RecoverableBuffer {
+            tab_id: header.tab_id,
+            path: header.path,
+            title: header.title,
+            content,
+            journal_modified,
+            file_modified,
+            conflict: file_modified.is_some_and(|modified| modified >= journal_modified),
+        });
+    }
+
+    recoverable.sort_by_key(|buffer| std::cmp::Reverse(buffer.journal_modified));
+    Ok(recoverable)
+}
@@ -0,0 +1,324 @@
+use regex::Regex;
+use std::path::Path;
+use std::sync::LazyLock;
+
+// Only the head of the file is needed; callers may send more
+const MAX_SAMPLE_CHARS: usize = 8 * 1024;
+// Vim looks for modelines in the first and last few lines
+const MODELINE_LINES: usize = 5;
+const HEURISTIC_LINES: usize = 50;
+
+static VIM_MODELINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|\s)(?:vi|vim|ex):.*?\b(?:ft|filetype|syntax)=([A-Za-z0-9_+.-]+)").unwrap()
+});
+static EMACS_MODELINE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"-\*-(.+?)-\*-").unwrap());
+static DOTENV_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:export\s+)?[A-Za-z_][A-Za-z0-9_.]*\s*=").unwrap());
+static INI_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:\[[^\]]+\]|[A-Za-z0-9_.\- ]+\s*[=:].*)$").unwrap());
+static MAKE_TARGET: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z0-9_./%$() -]+:(?:[^=]|$)").unwrap());
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DetectionSource {
+    Modeline,
+    Filename,
+    Extension,
+    Shebang,
+    Content,
+    Default,
+}
+
+#[derive(serde::Serialize)]
+pub struct LanguageDetection {
+    pub language: String,
+    // 0.0 to 1.0; how much the frontend should trust this over a user choice
+    confidence: f32,
+    source: DetectionSource,
+}
+
+impl LanguageDetection {
+    fn new(language: &str, confidence: f32, source: DetectionSource) -> Self {
+        Self {
+            language: language.to_string(),
+            confidence,
+            source,
+        }
+    }
+}
+
+/// Maps the names used by extensions, modelines and interpreters onto the
+/// language ids the editor understands.
+fn canonical_language(name: &str) -> Option<&'static str> {
+    let language = match name.to_ascii_lowercase().as_str() {
+        "md" | "markdown" | "mkd" => "markdown",
+        "txt" | "text" | "fundamental" => "text",
+        "log" => "log",
+        "ini" | "cfg" | "conf" | "dosini" | "editorconfig" | "properties" => "ini",
+        "json" | "jsonc" | "json5" => "json",
+        "jsonl" | "ndjson" => "jsonl",
+        "yaml" | "yml" => "yaml",
+        "xml" | "nxml" | "svg" => "xml",
+        "csv" => "csv",
+        "tsv" => "tsv",
+        "html" | "htm" | "xhtml" | "mhtml" => "html",
+        "css" | "scss" | "sass" | "less" => "css",
+        "js" | "jsx" | "mjs" | "cjs" | "javascript" | "js2" | "node" => "javascript",
+        "ts" | "tsx" | "mts" | "cts" | "typescript" => "typescript",
+        "py" | "pyw" | "python" => "python",
+        "rb" | "ruby" => "ruby",
+        "php" => "php",
+        "java" => "java",
+        "c" | "h" => "c",
+        "cpp" | "cc" | "cxx" | "hpp" | "hh" | "c++" => "cpp",
+        "rs" | "rust" => "rust",
+        "go" | "golang" => "go",
+        "swift" => "swift",
+        "kt" | "kts" | "kotlin" => "kotlin",
+        "scala" | "sc" => "scala",
+        "lua" => "lua",
+        "sh" | "bash" | "zsh" | "ksh" | "dash" | "fish" | "shell" | "shell-script" => "shell",
+        "ps1" | "psm1" | "powershell" | "pwsh" => "powershell",
+        "bat" | "cmd" | "dosbatch" | "batch" => "batch",
+        "sql" | "mysql" | "plsql" => "sql",
+        "graphql" | "gql" => "graphql",
+        "toml" => "toml",
+        "env" | "dotenv" => "dotenv",
+        "gitignore" | "dockerignore" | "npmignore" | "ignore" => "ignore",
+        "dockerfile" | "docker" => "dockerfile",
+        "make" | "makefile" | "mk" => "makefile",
+        "groovy" | "gradle" | "jenkinsfile" => "groovy",
+        "perl" | "pl" => "perl",
+        _ => return None,
+    };
+    Some(language)
+}
+
+fn language_for_filename(name: &str) -> Option<&'static str> {
+    let name = name.to_ascii_lowercase();
+    let language = match name.as_str() {
+        "dockerfile" | "containerfile" => "dockerfile",
+        "makefile" | "gnumakefile" => "makefile",
+        "jenkinsfile" => "groovy",
+        "vagrantfile" | "gemfile" | "rakefile" | "podfile" | "brewfile" => "ruby",
+        ".gitignore" | ".dockerignore" | ".npmignore" | ".prettierignore" | ".eslintignore" => "ignore",
+        ".editorconfig" | ".gitconfig" | ".npmrc" => "ini",
+        ".bashrc" | ".bash_profile" | ".zshrc" | ".zprofile" | ".profile" => "shell",
+        "cargo.lock" | "pipfile" => "toml",
+        ".env" => "dotenv",
+        // Variants such as .env.local, Dockerfile.dev or Makefile.inc
+        _ if name.starts_with(".env.") => "dotenv",
+        _ if name.starts_with("dockerfile.") => "dockerfile",
+        _ if name.starts_with("makefile.") => "makefile",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Detection from the path alone: well-known file names, then the extension.
+pub(crate) fn detect_from_path(path: &Path) -> Option<LanguageDetection> {
+    let name = path.file_name()?.to_str()?;
+
+    if let Some(language) = language_for_filename(name) {
+        return Some(LanguageDetection::new(language, 0.95, DetectionSource::Filename));
+    }
+
+    let extension = path.extension()?.to_str()?;
+    canonical_language(extension).map(|language| {
+        LanguageDetection::new(language, 0.8, DetectionSource::Extension)
+    })
+}
+
+fn detect_from_modeline(lines: &[&str]) -> Option<&'static str> {
+    let head = lines.iter().take(MODELINE_LINES);
+    let tail = lines.iter().rev().take(MODELINE_LINES);
+
+    for line in head.clone().chain(tail) {
+        if let Some(captures) = VIM_MODELINE.captures(line) {
+            if let Some(language) = canonical_language(&captures[1]) {
+                return Some(language);
+            }
+        }
+    }
+
+    // Emacs only honours the first line, or the second after a shebang
+    for line in lines.iter().take(2) {
+        let Some(captures) = EMACS_MODELINE.captures(line) else { continue };
+        let body = captures[1].trim();
+        let mode = if body.contains(':') {
+            body.split(';')
+                .filter_map(|part| part.split_once(':'))
+                .find(|(key, _)| key.trim().eq_ignore_ascii_case("mode"))
+                .map(|(_, value)| value.trim())
+        } else {
+            Some(body)
+        };
+        if let Some(language) = mode.and_then(|m| canonical_language(m.trim_end_matches("-mode"))) {
+            return Some(language);
+        }
+    }
+
+    None
+}
+
+fn detect_from_shebang(first_line: &str) -> Option<&'static str> {
+    let command = first_line.strip_prefix("#!")?;
+    let mut parts = command.split_whitespace();
+    let mut interpreter = parts.next()?.rsplit('/').next()?;
+
+    // #!/usr/bin/env [-S] python3
+    if interpreter == "env" {
+        interpreter = parts.find(|part| !part.starts_with('-') && !part.contains('='))?;
+    }
+
+    // python3.12 → python, node18 → node
+    let name = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    match name {
+        "deno" | "bun" | "nodejs" => Some("javascript"),
+        "ts-node" | "tsx" => Some("typescript"),
+        _ => canonical_language(name),
+    }
+}
+
+fn looks_like_jsonl(lines: &[&str]) -> bool {
+    let records: Vec<&str> = lines.iter().map(|l| l.trim()).filter(|l| !l.is_empty()).collect();
+    // The sample may end mid-record, so the last line is allowed to be partial
+    let complete = &records[..records.len().saturating_sub(1)];
+    complete.len() >= 2
+        && complete.iter().all(|line| {
+            line.starts_with('{') && serde_json::from_str::<serde_json::Value>(line).is_ok()
+        })
+}
+
+fn delimited_columns(lines: &[&str], delimiter: char) -> bool {
+    let counts: Vec<usize> = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .take(20)
+        .map(|line| line.matches(delimiter).count())
+        .collect();
+    counts.len() >= 2 && counts[0] > 0 && counts.iter().all(|&c| c == counts[0])
+}
+
+fn detect_from_content(text: &str, lines: &[&str]) -> Option<(&'static str, f32)> {
+    let trimmed = text.trim_start();
+    let lower_head: String = trimmed.chars().take(256).collect::<String>().to_ascii_lowercase();
+
+    if lower_head.starts_with("<?xml") {
+        return Some(("xml", 0.9));
+    }
+    if lower_head.starts_with("<!doctype html") || lower_head.starts_with("<html") {
+        return Some(("html", 0.9));
+    }
+    if trimmed.starts_with("<?php") {
+        return Some(("php", 0.9));
+    }
+
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        if serde_json::from_str::<serde_json::Value>(text).is_ok() {
+            return Some(("json", 0.85));
+        }
+        if looks_like_jsonl(lines) {
+            return Some(("jsonl", 0.8));
+        }
+    }
+
+    let code_lines: Vec<&str> = lines
+        .iter()
+        .take(HEURISTIC_LINES)
+        .map(|l| l.trim_end())
+        .filter(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .collect();
+    if code_lines.is_empty() {
+        return None;
+    }
+
+    let first_instruction = code_lines[0].split_whitespace().next().unwrap_or("");
+    if first_instruction.eq_ignore_ascii_case("FROM") || first_instruction.eq_ignore_ascii_case("ARG") {
+        let instructions = ["FROM", "RUN", "COPY", "ADD", "CMD", "ENTRYPOINT", "WORKDIR", "ENV", "EXPOSE"];
+        let hits = code_lines
+            .iter()
+            .filter(|l| instructions.iter().any(|i| l.starts_with(&format!("{} ", i))))
+            .count();
+        if hits >= 2 {
+            return Some(("dockerfile", 0.7));
+        }
+    }
+
+    if code_lines.iter().all(|l| DOTENV_LINE.is_match(l)) {
+        return Some(("dotenv", 0.6));
+    }
+
+    if code_lines[0].starts_with('[') && code_lines.iter().all(|l| INI_LINE.is_match(l.trim())) {
+        return Some(("ini", 0.6));
+    }
+
+    let has_target = code_lines.iter().any(|l| MAKE_TARGET.is_match(l) && !l.starts_with('\t'));
+    let has_recipe = code_lines.iter().any(|l| l.starts_with('\t'));
+    if has_target && has_recipe {
+        return Some(("makefile", 0.5));
+    }
+
+    if trimmed.starts_with("---\n") && lines.iter().skip(1).any(|l| l.trim() == "---") {
+        // Front matter followed by prose is far more common than a YAML stream
+        return Some(("markdown", 0.5));
+    }
+    let markdown_lines = lines
+        .iter()
+        .take(HEURISTIC_LINES)
+        .filter(|l| {
+            l.starts_with("# ") || l.starts_with("## ") || l.starts_with("```") || l.starts_with("- [")
+        })
+        .count();
+    if markdown_lines >= 2 {
+        return Some(("markdown", 0.5));
+    }
+
+    if delimited_columns(lines, '\t') {
+        return Some(("tsv", 0.4));
+    }
+    if delimited_columns(lines, ',') {
+        return Some(("csv", 0.4));
+    }
+
+    None
+}
+
+fn sample(content: &str) -> &str {
+    match content.char_indices().nth(MAX_SAMPLE_CHARS) {
+        Some((index, _)) => &content[..index],
+        None => content,
+    }
+}
+
+/// Detects the language of a file from its path and the head of its content.
+/// Modelines win over everything since they are an explicit choice, then
+/// well-known file names and extensions. Shebangs and content heuristics are
+/// only consulted when the path is missing or says nothing specific.
+#[tauri::command]
+pub fn detect_language(path: Option<String>, content: Option<String>) -> LanguageDetection {
+    let text = sample(content.as_deref().unwrap_or(""));
+    let lines: Vec<&str> = text.lines().collect();
+
+    if let Some(language) = detect_from_modeline(&lines) {
+        return LanguageDetection::new(language, 1.0, DetectionSource::Modeline);
+    }
+
+    let from_path = path.as_deref().and_then(|p| detect_from_path(Path::new(p)));
+    match from_path {
+        Some(detection) if detection.language != "text" => return detection,
+        _ => {}
+    }
+
+    if let Some(language) = lines.first().and_then(|line| detect_from_shebang(line)) {
+        return LanguageDetection::new(language, 0.9, DetectionSource::Shebang);
+    }
+
+    if let Some((language, confidence)) = detect_from_content(text, &lines) {
+        return LanguageDetection::new(language, confidence, DetectionSource::Content);
+    }
+
+    // An explicit .txt is still a confident answer
+    from_path.unwrap_or_else(|| LanguageDetection::new("text", 0.1, DetectionSource::Default))
+}
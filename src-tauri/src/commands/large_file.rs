@@ -0,0 +1,371 @@
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use tauri::{AppHandle, Emitter, State};
+
+use super::search::{build_matcher, SearchOptions};
+use crate::encoding;
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+const PROGRESS_INTERVAL: u64 = 32 * 1024 * 1024;
+const SAMPLE_SIZE: usize = 64 * 1024;
+const DEFAULT_MAX_RESULTS: usize = 1000;
+
+// Line numbers and columns in this module are zero-based; columns count chars
+
+struct LargeFile {
+    path: PathBuf,
+    size: u64,
+    encoding: &'static Encoding,
+    bom_length: u64,
+    // Byte offset at which each line starts
+    line_offsets: RwLock<Vec<u64>>,
+    indexed: AtomicBool,
+    closed: AtomicBool,
+}
+
+impl LargeFile {
+    fn newline(&self) -> &'static [u8] {
+        if self.encoding == UTF_16LE {
+            &[0x0A, 0x00]
+        } else if self.encoding == UTF_16BE {
+            &[0x00, 0x0A]
+        } else {
+            b"\n"
+        }
+    }
+
+    // The last offset is only a complete line once indexing has finished
+    fn available_lines(&self) -> usize {
+        let offsets = self.line_offsets.read().unwrap().len();
+        if self.indexed.load(Ordering::Acquire) {
+            offsets
+        } else {
+            offsets.saturating_sub(1)
+        }
+    }
+
+    fn info(&self, handle: u64) -> LargeFileInfo {
+        LargeFileInfo {
+            handle,
+            path: self.path.to_string_lossy().to_string(),
+            size: self.size,
+            encoding: self.encoding.name().to_string(),
+            line_count: self.available_lines(),
+            indexed: self.indexed.load(Ordering::Acquire),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LargeFileState {
+    next_handle: AtomicU64,
+    files: Mutex<HashMap<u64, Arc<LargeFile>>>,
+}
+
+impl LargeFileState {
+    fn get(&self, handle: u64) -> Result<Arc<LargeFile>, String> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(&handle)
+            .cloned()
+            .ok_or_else(|| "Invalid large file handle".to_string())
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct LargeFileInfo {
+    handle: u64,
+    path: String,
+    size: u64,
+    encoding: String,
+    line_count: usize,
+    indexed: bool,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct LargeFileProgress {
+    handle: u64,
+    indexed_bytes: u64,
+    total_bytes: u64,
+    line_count: usize,
+    done: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct LineRange {
+    start_line: usize,
+    lines: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct LargeFileMatch {
+    line: usize,
+    column: usize,
+    length: usize,
+    preview: String,
+}
+
+// Reads until the buffer is full or EOF, so chunk boundaries stay aligned
+// to UTF-16 code units
+fn fill_buffer(file: &mut File, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        let read = file.read(&mut buffer[filled..])?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
+    }
+    Ok(filled)
+}
+
+fn index_lines(app: &AppHandle, handle: u64, large_file: &LargeFile) -> std::io::Result<()> {
+    let mut file = File::open(&large_file.path)?;
+    file.seek(SeekFrom::Start(large_file.bom_length))?;
+
+    let newline = large_file.newline();
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut position = large_file.bom_length;
+    let mut next_progress = PROGRESS_INTERVAL;
+
+    loop {
+        if large_file.closed.load(Ordering::Acquire) {
+            return Ok(());
+        }
+
+        let read = fill_buffer(&mut file, &mut buffer)?;
+        if read == 0 {
+            break;
+        }
+
+        let chunk_offsets: Vec<u64> = buffer[..read]
+            .chunks_exact(newline.len())
+            .enumerate()
+            .filter(|(_, unit)| *unit == newline)
+            .map(|(i, _)| position + ((i + 1) * newline.len()) as u64)
+            .collect();
+
+        large_file.line_offsets.write().unwrap().extend(chunk_offsets);
+        position += read as u64;
+
+        if position >= next_progress {
+            next_progress += PROGRESS_INTERVAL;
+            let _ = app.emit(
+                "large-file-progress",
+                LargeFileProgress {
+                    handle,
+                    indexed_bytes: position,
+                    total_bytes: large_file.size,
+                    line_count: large_file.available_lines(),
+                    done: false,
+                },
+            );
+        }
+    }
+
+    large_file.indexed.store(true, Ordering::Release);
+    let _ = app.emit(
+        "large-file-progress",
+        LargeFileProgress {
+            handle,
+            indexed_bytes: position,
+            total_bytes: large_file.size,
+            line_count: large_file.available_lines(),
+            done: true,
+        },
+    );
+
+    Ok(())
+}
+
+fn split_lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = text
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line).to_string())
+        .collect();
+
+    // A range ending on a newline doesn't include the following empty line
+    if text.ends_with('\n') {
+        lines.pop();
+    }
+    lines
+}
+
+#[tauri::command]
+pub fn open_large_file(
+    app: AppHandle,
+    state: State<'_, LargeFileState>,
+    path: String,
+) -> Result<LargeFileInfo, String> {
+    let path_buf = PathBuf::from(&path);
+    let mut file = File::open(&path_buf)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    let size = file.metadata()
+        .map_err(|e| format!("Failed to get file metadata: {}", e))?
+        .len();
+
+    let mut sample = vec![0u8; SAMPLE_SIZE];
+    let sampled = fill_buffer(&mut file, &mut sample)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let (detected, bom_length) = encoding::detect_encoding(&sample[..sampled]);
+
+    let large_file = Arc::new(LargeFile {
+        path: path_buf,
+        size,
+        encoding: detected,
+        bom_length: bom_length as u64,
+        line_offsets: RwLock::new(vec![bom_length as u64]),
+        indexed: AtomicBool::new(false),
+        closed: AtomicBool::new(false),
+    });
+
+    let handle = state.next_handle.fetch_add(1, Ordering::Relaxed) + 1;
+    state.files.lock().unwrap().insert(handle, large_file.clone());
+
+    let info = large_file.info(handle);
+    std::thread::spawn(move || {
+        if let Err(e) = index_lines(&app, handle, &large_file) {
+            eprintln!("Failed to index {}: {}", large_file.path.display(), e);
+        }
+    });
+
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn get_large_file_info(state: State<'_, LargeFileState>, handle: u64) -> Result<LargeFileInfo, String> {
+    Ok(state.get(handle)?.info(handle))
+}
+
+#[tauri::command]
+pub fn read_large_file_lines(
+    state: State<'_, LargeFileState>,
+    handle: u64,
+    start_line: usize,
+    line_count: usize,
+) -> Result<LineRange, String> {
+    let large_file = state.get(handle)?;
+    let available = large_file.available_lines();
+    let start = start_line.min(available);
+    let end = start.saturating_add(line_count).min(available);
+
+    if start == end {
+        return Ok(LineRange { start_line: start, lines: Vec::new() });
+    }
+
+    let (from, to) = {
+        let offsets = large_file.line_offsets.read().unwrap();
+        (offsets[start], offsets.get(end).copied().unwrap_or(large_file.size))
+    };
+
+    let mut file = File::open(&large_file.path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    file.seek(SeekFrom::Start(from))
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let mut bytes = vec![0u8; (to - from) as usize];
+    let read = fill_buffer(&mut file, &mut bytes)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    bytes.truncate(read);
+
+    let (text, _) = large_file.encoding.decode_without_bom_handling(&bytes);
+
+    Ok(LineRange {
+        start_line: start,
+        lines: split_lines(&text),
+    })
+}
+
+fn search_file(
+    large_file: &LargeFile,
+    matcher: &Regex,
+    max_results: usize,
+) -> std::io::Result<Vec<LargeFileMatch>> {
+    let mut file = File::open(&large_file.path)?;
+    file.seek(SeekFrom::Start(large_file.bom_length))?;
+
+    let mut decoder = large_file.encoding.new_decoder_without_bom_handling();
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut pending = String::new();
+    let mut line_number = 0;
+    let mut matches = Vec::new();
+
+    let search_line = |line: &str, line_number: usize, matches: &mut Vec<LargeFileMatch>| {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        for found in matcher.find_iter(line) {
+            if matches.len() >= max_results {
+                return;
+            }
+            matches.push(LargeFileMatch {
+                line: line_number,
+                column: line[..found.start()].chars().count(),
+                length: found.as_str().chars().count(),
+                preview: line.chars().take(500).collect(),
+            });
+        }
+    };
+
+    loop {
+        if large_file.closed.load(Ordering::Acquire) || matches.len() >= max_results {
+            break;
+        }
+
+        let read = fill_buffer(&mut file, &mut buffer)?;
+        let last = read == 0;
+
+        let capacity = decoder
+            .max_utf8_buffer_length(read)
+            .unwrap_or(read * 3 + 16);
+        pending.reserve(capacity);
+        let _ = decoder.decode_to_string(&buffer[..read], &mut pending, last);
+
+        // Search every complete line, keeping the trailing partial line for the next chunk
+        let complete = if last { pending.len() } else { pending.rfind('\n').map_or(0, |i| i + 1) };
+        for line in pending[..complete].split_inclusive('\n') {
+            search_line(line.strip_suffix('\n').unwrap_or(line), line_number, &mut matches);
+            if line.ends_with('\n') {
+                line_number += 1;
+            }
+        }
+        pending.drain(..complete);
+
+        if last {
+            break;
+        }
+    }
+
+    Ok(matches)
+}
+
+#[tauri::command]
+pub async fn search_large_file(
+    state: State<'_, LargeFileState>,
+    handle: u64,
+    query: String,
+    options: Option<SearchOptions>,
+    max_results: Option<usize>,
+) -> Result<Vec<LargeFileMatch>, String> {
+    let large_file = state.get(handle)?;
+    let matcher = build_matcher(&query, &options.unwrap_or_default())?;
+    let max_results = max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+
+    tauri::async_runtime::spawn_blocking(move || search_file(&large_file, &matcher, max_results))
+        .await
+        .map_err(|e| format!("Search failed: {}", e))?
+        .map_err(|e| format!("Failed to search file: {}", e))
+}
+
+#[tauri::command]
+pub fn close_large_file(state: State<'_, LargeFileState>, handle: u64) -> Result<(), String> {
+    if let Some(large_file) = state.files.lock().unwrap().remove(&handle) {
+        large_file.closed.store(true, Ordering::Release);
+    }
+    Ok(())
+}
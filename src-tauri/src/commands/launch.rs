@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::cli::{self, OpenFailure, OpenRequest};
+use crate::deep_link::{self, DeepLink};
+
+/// Emitted after something is queued; the payload-free signal to call
+/// `take_pending_open_requests`.
+pub const PENDING_EVENT: &str = "open-requests-pending";
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PendingOpen {
+    OpenFiles { request: OpenRequest },
+    OpenFilesFailed { failures: Vec<OpenFailure> },
+    // contextpad://new
+    NewTab {
+        title: Option<String>,
+        content: String,
+    },
+    // contextpad://template/<id>, filled in with `variables`
+    Template {
+        id: String,
+        variables: HashMap<String, String>,
+    },
+    InvalidLink { url: String, reason: String },
+    // Launch arguments that didn't parse
+    InvalidArguments { reason: String },
+}
+
+/// Requests to open something that arrived from outside the window: the
+/// first launch's arguments, launches forwarded by the single-instance
+/// plugin, and deep links. They wait here until the frontend takes them,
+/// so none are lost while the window is still loading.
+#[derive(Default)]
+pub struct LaunchState {
+    queue: Mutex<Vec<PendingOpen>>,
+}
+
+pub fn queue(app: &AppHandle, item: PendingOpen) {
+    app.state::<LaunchState>().queue.lock().unwrap().push(item);
+    let _ = app.emit(PENDING_EVENT, ());
+}
+
+/// Queues what a launch's arguments ask for, with paths resolved against
+/// `cwd`, the directory the launch ran in.
+pub fn queue_args<I, S>(app: &AppHandle, args: I, cwd: &Path)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let request = match cli::parse_args(args) {
+        Ok(request) => request,
+        Err(e) => {
+            eprintln!("contextpad: {}", e);
+            queue(app, PendingOpen::InvalidArguments { reason: e });
+            return;
+        }
+    };
+
+    queue_resolved(app, request, cwd);
+}
+
+fn queue_resolved(app: &AppHandle, request: OpenRequest, cwd: &Path) {
+    let (request, failures) = request.resolve(cwd);
+    if !request.is_empty() {
+        queue(app, PendingOpen::OpenFiles { request });
+    }
+    if !failures.is_empty() {
+        queue(app, PendingOpen::OpenFilesFailed { failures });
+    }
+}
+
+/// Queues what a `contextpad://` link asks for, or why it can't be followed.
+pub fn queue_link(app: &AppHandle, url: &str) {
+    let item = match deep_link::parse(url) {
+        // Link paths are already absolute, so there's nothing to resolve against
+        Ok(DeepLink::Open(request)) => return queue_resolved(app, request, Path::new("")),
+        Ok(DeepLink::New { title, content }) => PendingOpen::NewTab { title, content },
+        Ok(DeepLink::Template { id, variables }) => PendingOpen::Template { id, variables },
+        Err(reason) => PendingOpen::InvalidLink {
+            url: url.to_string(),
+            reason,
+        },
+    };
+    queue(app, item);
+}
+
+/// Hands over everything queued so far, oldest first. The frontend calls
+/// this once its listeners are mounted and again on each PENDING_EVENT.
+#[tauri::command]
+pub fn take_pending_open_requests(launch: State<'_, LaunchState>) -> Vec<PendingOpen> {
+    std::mem::take(&mut *launch.queue.lock().unwrap())
+}
@@ -0,0 +1,238 @@
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+use std::path::Path;
+use tauri::State;
+
+use super::history::HistoryState;
+use crate::encoding;
+
+/// A run of merged lines. Conflicts carry all three versions so the UI can
+/// let the user pick one or combine them.
+#[derive(serde::Serialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum MergeHunk {
+    Clean {
+        text: String,
+    },
+    Conflict {
+        base: String,
+        ours: String,
+        theirs: String,
+    },
+}
+
+#[derive(serde::Serialize)]
+pub struct MergeResult {
+    // Set when every change merged cleanly
+    merged: Option<String>,
+    // The whole file, in order; joining the hunks rebuilds it once each
+    // conflict has been resolved
+    hunks: Vec<MergeHunk>,
+    conflicts: usize,
+    // What was on disk, which becomes the new base for the buffer
+    theirs: String,
+}
+
+fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+// For each base line, the line it matches in `other`, if it survived unchanged
+fn line_matches(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; base.len()];
+    for op in capture_diff_slices(Algorithm::Myers, base, other) {
+        if let DiffOp::Equal { old_index, new_index, len } = op {
+            for offset in 0..len {
+                matches[old_index + offset] = Some(new_index + offset);
+            }
+        }
+    }
+    matches
+}
+
+fn push_clean(hunks: &mut Vec<MergeHunk>, lines: &[&str]) {
+    if lines.is_empty() {
+        return;
+    }
+    match hunks.last_mut() {
+        Some(MergeHunk::Clean { text }) => text.push_str(&lines.concat()),
+        _ => hunks.push(MergeHunk::Clean { text: lines.concat() }),
+    }
+}
+
+/// Line-based three-way merge (diff3). Regions where base, ours and theirs
+/// all agree are stable; between them, a side that left the base untouched
+/// takes the other side's change, identical changes merge, and anything else
+/// is a conflict.
+pub(crate) fn merge3(base: &str, ours: &str, theirs: &str) -> Vec<MergeHunk> {
+    let base_lines = split_lines(base);
+    let our_lines = split_lines(ours);
+    let their_lines = split_lines(theirs);
+    let ours_at = line_matches(&base_lines, &our_lines);
+    let theirs_at = line_matches(&base_lines, &their_lines);
+
+    let mut hunks = Vec::new();
+    let (mut b, mut o, mut t) = (0, 0, 0);
+
+    loop {
+        // Next base line that both sides kept
+        let stable = (b..base_lines.len()).find_map(|i| match (ours_at[i], theirs_at[i]) {
+            (Some(oi), Some(ti)) => Some((i, oi, ti)),
+            _ => None,
+        });
+        let (b_end, o_end, t_end) = stable.unwrap_or((base_lines.len(), our_lines.len(), their_lines.len()));
+
+        if (b_end, o_end, t_end) != (b, o, t) {
+            let base_chunk = &base_lines[b..b_end];
+            let our_chunk = &our_lines[o..o_end];
+            let their_chunk = &their_lines[t..t_end];
+
+            if our_chunk == base_chunk || our_chunk == their_chunk {
+                push_clean(&mut hunks, their_chunk);
+            } else if their_chunk == base_chunk {
+                push_clean(&mut hunks, our_chunk);
+            } else {
+                hunks.push(MergeHunk::Conflict {
+                    base: base_chunk.concat(),
+                    ours: our_chunk.concat(),
+                    theirs: their_chunk.concat(),
+                });
+            }
+        }
+
+        if stable.is_none() {
+            break;
+        }
+        push_clean(&mut hunks, &base_lines[b_end..b_end + 1]);
+        (b, o, t) = (b_end + 1, o_end + 1, t_end + 1);
+    }
+
+    hunks
+}
+
+/// Merges the buffer with a file that changed on disk since `base` (the
+/// content as of the last load or save). Without a base, the newest saved
+/// revision from local history stands in.
+#[tauri::command]
+pub async fn merge_file_changes(
+    snapshots: State<'_, HistoryState>,
+    path: String,
+    base: Option<String>,
+    buffer: String,
+) -> Result<MergeResult, String> {
+    let bytes = std::fs::read(&path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let theirs = encoding::decode(&bytes).content;
+
+    let base = match base {
+        Some(base) => base,
+        None => snapshots
+            .latest_saved(Path::new(&path))?
+            .map(|bytes| encoding::decode(&bytes).content)
+            .ok_or_else(|| "No base version to merge against".to_string())?,
+    };
+
+    let hunks = merge3(&base, &buffer, &theirs);
+    let conflicts = hunks
+        .iter()
+        .filter(|hunk| matches!(hunk, MergeHunk::Conflict { .. }))
+        .count();
+    let merged = (conflicts == 0).then(|| {
+        hunks
+            .iter()
+            .map(|hunk| match hunk {
+                MergeHunk::Clean { text } => text.as_str(),
+                MergeHunk::Conflict { .. } => "",
+            })
+            .collect()
+    });
+
+    Ok(MergeResult {
+        merged,
+        hunks,
+        conflicts,
+        theirs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clean(text: &str) -> MergeHunk {
+        MergeHunk::Clean { text: text.to_string() }
+    }
+
+    #[test]
+    fn takes_changes_from_both_sides() {
+        let base = "a\nb\nc\nd\ne\n";
+        let ours = "A\nb\nc\nd\ne\n";
+        let theirs = "a\nb\nc\nd\nE\n";
+        assert_eq!(merge3(base, ours, theirs), vec![clean("A\nb\nc\nd\nE\n")]);
+    }
+
+    #[test]
+    fn keeps_insertions_and_deletions() {
+        let base = "a\nb\nc\n";
+        let ours = "a\nnew\nb\nc\n";
+        let theirs = "a\nb\n";
+        assert_eq!(merge3(base, ours, theirs), vec![clean("a\nnew\nb\n")]);
+    }
+
+    #[test]
+    fn unchanged_side_takes_the_other() {
+        let base = "a\nb\n";
+        assert_eq!(merge3(base, base, "x\n"), vec![clean("x\n")]);
+        assert_eq!(merge3(base, "y\n", base), vec![clean("y\n")]);
+    }
+
+    #[test]
+    fn identical_edits_merge_once() {
+        let base = "a\nb\nc\n";
+        let edited = "a\nB\nc\n";
+        assert_eq!(merge3(base, edited, edited), vec![clean(edited)]);
+    }
+
+    #[test]
+    fn overlapping_edits_conflict() {
+        let base = "a\nb\nc\n";
+        let ours = "a\nours\nc\n";
+        let theirs = "a\ntheirs\nc\n";
+        assert_eq!(
+            merge3(base, ours, theirs),
+            vec![
+                clean("a\n"),
+                MergeHunk::Conflict {
+                    base: "b\n".to_string(),
+                    ours: "ours\n".to_string(),
+                    theirs: "theirs\n".to_string(),
+                },
+                clean("c\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn edit_against_deletion_conflicts() {
+        let base = "a\nb\nc\n";
+        let hunks = merge3(base, "a\nchanged\nc\n", "a\nc\n");
+        assert_eq!(
+            hunks[1],
+            MergeHunk::Conflict {
+                base: "b\n".to_string(),
+                ours: "changed\n".to_string(),
+                theirs: String::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn conflict_keeps_the_rest_clean() {
+        let base = "1\n2\n3\n4\n5\n";
+        let ours = "one\n2\n3\n4\nfive\n";
+        let theirs = "1\n2\n3\n4\nFIVE\n";
+        let hunks = merge3(base, ours, theirs);
+        assert_eq!(hunks[0], clean("one\n2\n3\n4\n"));
+        assert!(matches!(hunks[1], MergeHunk::Conflict { .. }));
+        assert_eq!(hunks.len(), 2);
+    }
+}
@@ -1,3 +1,19 @@
 pub mod window;
 pub mod file;
 pub mod secrets;
+pub mod watcher;
+pub mod large_file;
+pub mod walk;
+pub mod search;
+pub mod file_index;
+pub mod language;
+pub mod editorconfig;
+pub mod history;
+pub mod journal;
+pub mod storage;
+pub mod profile;
+pub mod merge;
+pub mod diff;
+pub mod git;
+pub mod launch;
+pub mod tokens;
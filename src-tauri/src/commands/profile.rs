@@ -0,0 +1,469 @@
+use rusqlite::Connection;
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::file::atomic_write;
+use super::secrets::has_api_key;
+use super::storage::{get_value, set_value, StorageState};
+
+// A .contextpad-profile is a zip holding manifest.json plus one JSON file per
+// exported section. Sections are read from and written back to the keys the
+// frontend stores persist under.
+
+const PROFILE_FORMAT: &str = "contextpad-profile";
+const PROFILE_VERSION: u32 = 1;
+const MANIFEST_FILE: &str = "manifest.json";
+// Guards against decompressing absurdly large entries from a hostile archive
+const MAX_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+
+const TEMPLATES_KEY: &str = "contextpad-templates";
+const ACTIONS_KEY: &str = "contextpad-actions";
+const TABS_KEY: &str = "contextpad-tabs-v2";
+const SETTINGS_KEY: &str = "contextpad-settings";
+
+// Providers whose API keys live in the OS keyring (see TokenSettings.tsx)
+const SECRET_PROVIDERS: &[&str] = &["anthropic", "google"];
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileSection {
+    Templates,
+    Actions,
+    PinnedTabs,
+    Settings,
+    CustomModels,
+    Dictionary,
+    SecretProviders,
+}
+
+impl ProfileSection {
+    const ALL: [ProfileSection; 7] = [
+        ProfileSection::Templates,
+        ProfileSection::Actions,
+        ProfileSection::PinnedTabs,
+        ProfileSection::Settings,
+        ProfileSection::CustomModels,
+        ProfileSection::Dictionary,
+        ProfileSection::SecretProviders,
+    ];
+
+    fn file_name(self) -> &'static str {
+        match self {
+            ProfileSection::Templates => "templates.json",
+            ProfileSection::Actions => "actions.json",
+            ProfileSection::PinnedTabs => "pinned_tabs.json",
+            ProfileSection::Settings => "settings.json",
+            ProfileSection::CustomModels => "custom_models.json",
+            ProfileSection::Dictionary => "dictionary.json",
+            ProfileSection::SecretProviders => "secret_providers.json",
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ProfileManifest {
+    format: String,
+    version: u32,
+    app_version: String,
+    created_at: u64,
+    sections: Vec<ProfileSection>,
+}
+
+#[derive(serde::Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    Merge,
+    Replace,
+}
+
+fn read_json(conn: &Connection, key: &str) -> Result<Option<Value>, String> {
+    Ok(get_value(conn, key)?.and_then(|text| serde_json::from_str(&text).ok()))
+}
+
+fn write_json(conn: &Connection, key: &str, value: &Value) -> Result<(), String> {
+    set_value(conn, key, &value.to_string())
+}
+
+/// The object at `path` inside `value`, created (replacing any non-object)
+/// along the way.
+fn object_at<'a>(value: &'a mut Value, path: &[&str]) -> &'a mut Map<String, Value> {
+    let mut current = value;
+    for key in path {
+        if !current.is_object() {
+            *current = Value::Object(Map::new());
+        }
+        current = current
+            .as_object_mut()
+            .unwrap()
+            .entry(key.to_string())
+            .or_insert(Value::Null);
+    }
+    if !current.is_object() {
+        *current = Value::Object(Map::new());
+    }
+    current.as_object_mut().unwrap()
+}
+
+fn lookup<'a>(value: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |current, key| current.get(key))
+}
+
+// Objects merge key by key with the incoming side winning; anything else is replaced
+fn merge_values(target: &mut Value, incoming: Value) {
+    match (target, incoming) {
+        (Value::Object(target), Value::Object(incoming)) => {
+            for (key, value) in incoming {
+                merge_values(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (target, incoming) => *target = incoming,
+    }
+}
+
+fn update_value(target: &mut Value, incoming: Value, replace: bool) {
+    if replace {
+        *target = incoming;
+    } else {
+        merge_values(target, incoming);
+    }
+}
+
+fn restore_field(target: &mut Map<String, Value>, key: &str, value: Option<Value>) {
+    match value {
+        Some(value) => target.insert(key.to_string(), value),
+        None => target.remove(key),
+    };
+}
+
+// Items with a matching id are replaced in place; new ones are appended
+fn merge_by_id(existing: Option<&Value>, incoming: Value) -> Value {
+    let mut merged = existing.and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    for item in incoming.as_array().cloned().unwrap_or_default() {
+        match merged.iter_mut().find(|current| current.get("id") == item.get("id")) {
+            Some(current) => *current = item,
+            None => merged.push(item),
+        }
+    }
+    Value::Array(merged)
+}
+
+fn merge_strings(existing: Option<&Value>, incoming: Value) -> Value {
+    let mut merged = existing.and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    for item in incoming.as_array().cloned().unwrap_or_default() {
+        if !merged.contains(&item) {
+            merged.push(item);
+        }
+    }
+    Value::Array(merged)
+}
+
+fn export_section(conn: &Connection, section: ProfileSection) -> Result<Value, String> {
+    let tabs = read_json(conn, TABS_KEY)?.unwrap_or(Value::Null);
+    let settings = read_json(conn, SETTINGS_KEY)?.unwrap_or(Value::Null);
+    let field = |value: &Value, path: &[&str], default: Value| lookup(value, path).cloned().unwrap_or(default);
+
+    Ok(match section {
+        ProfileSection::Templates => read_json(conn, TEMPLATES_KEY)?.unwrap_or(json!([])),
+        ProfileSection::Actions => read_json(conn, ACTIONS_KEY)?.unwrap_or(json!([])),
+        ProfileSection::PinnedTabs => json!({
+            "pinnedTabs": field(&tabs, &["pinnedTabs"], json!([])),
+            "pinnedCategoryOrder": field(&tabs, &["pinnedCategoryOrder"], json!([])),
+            "pinnedCollapsedCategories": field(&tabs, &["pinnedCollapsedCategories"], json!([])),
+        }),
+        ProfileSection::Settings => {
+            // Custom models and the dictionary are sections of their own
+            let mut app = field(&settings, &["state"], json!({}));
+            if let Some(app) = app.as_object_mut() {
+                app.remove("customModels");
+            }
+            let mut view = field(&tabs, &["viewSettings"], json!({}));
+            if let Some(spell_check) = view.get_mut("spellCheckConfig").and_then(|v| v.as_object_mut()) {
+                spell_check.remove("customDictionary");
+            }
+            json!({
+                "app": app,
+                "app_store_version": field(&settings, &["version"], Value::Null),
+                "view": view,
+            })
+        }
+        ProfileSection::CustomModels => field(&settings, &["state", "customModels"], json!([])),
+        ProfileSection::Dictionary => {
+            field(&tabs, &["viewSettings", "spellCheckConfig", "customDictionary"], json!([]))
+        }
+        ProfileSection::SecretProviders => json!(SECRET_PROVIDERS
+            .iter()
+            .filter(|provider| has_api_key(provider.to_string()).unwrap_or(false))
+            .collect::<Vec<_>>()),
+    })
+}
+
+fn validate_items_with_ids(value: &Value, what: &str) -> Result<(), String> {
+    let items = value.as_array().ok_or_else(|| format!("{} must be a list", what))?;
+    for item in items {
+        if !item.get("id").is_some_and(|id| id.is_string()) {
+            return Err(format!("Every entry in {} needs a string id", what));
+        }
+    }
+    Ok(())
+}
+
+fn validate_strings(value: Option<&Value>, what: &str) -> Result<(), String> {
+    match value {
+        None => Ok(()),
+        Some(Value::Array(items)) if items.iter().all(|item| item.is_string()) => Ok(()),
+        Some(_) => Err(format!("{} must be a list of strings", what)),
+    }
+}
+
+fn validate_section(section: ProfileSection, value: &Value) -> Result<(), String> {
+    match section {
+        ProfileSection::Templates => validate_items_with_ids(value, "templates"),
+        ProfileSection::Actions => validate_items_with_ids(value, "actions"),
+        ProfileSection::CustomModels => validate_items_with_ids(value, "custom models"),
+        ProfileSection::PinnedTabs => {
+            if !value.is_object() {
+                return Err("Pinned tabs must be an object".to_string());
+            }
+            validate_items_with_ids(value.get("pinnedTabs").unwrap_or(&json!([])), "pinned tabs")?;
+            validate_strings(value.get("pinnedCategoryOrder"), "pinned category order")?;
+            validate_strings(value.get("pinnedCollapsedCategories"), "collapsed categories")
+        }
+        ProfileSection::Settings => {
+            let valid = value.is_object()
+                && ["app", "view"]
+                    .iter()
+                    .all(|key| value.get(key).is_none_or(|v| v.is_object()));
+            if valid {
+                Ok(())
+            } else {
+                Err("Settings must contain app and view objects".to_string())
+            }
+        }
+        ProfileSection::Dictionary => validate_strings(Some(value), "dictionary"),
+        ProfileSection::SecretProviders => validate_strings(Some(value), "secret providers"),
+    }
+}
+
+fn apply_section(conn: &Connection, section: ProfileSection, value: Value, mode: ImportMode) -> Result<(), String> {
+    let replace = matches!(mode, ImportMode::Replace);
+
+    match section {
+        ProfileSection::Templates | ProfileSection::Actions => {
+            let key = if section == ProfileSection::Templates { TEMPLATES_KEY } else { ACTIONS_KEY };
+            let existing = read_json(conn, key)?;
+            let merged = if replace { value } else { merge_by_id(existing.as_ref(), value) };
+            write_json(conn, key, &merged)
+        }
+        ProfileSection::PinnedTabs => {
+            let mut tabs = read_json(conn, TABS_KEY)?.unwrap_or(json!({}));
+            let target = object_at(&mut tabs, &[]);
+            for key in ["pinnedTabs", "pinnedCategoryOrder", "pinnedCollapsedCategories"] {
+                let Some(incoming) = value.get(key).cloned() else { continue };
+                let merged = match (replace, key) {
+                    (true, _) => incoming,
+                    (false, "pinnedTabs") => merge_by_id(target.get(key), incoming),
+                    (false, _) => merge_strings(target.get(key), incoming),
+                };
+                target.insert(key.to_string(), merged);
+            }
+            write_json(conn, TABS_KEY, &tabs)
+        }
+        ProfileSection::Settings => {
+            // Custom models and the dictionary are sections of their own, so
+            // they survive a settings import untouched
+            if let Some(app) = value.get("app").cloned() {
+                let mut settings = read_json(conn, SETTINGS_KEY)?.unwrap_or(json!({}));
+                let root = object_at(&mut settings, &[]);
+                if !root.contains_key("version") {
+                    // Lets the settings store run its own migrations on load
+                    let version = value.get("app_store_version").cloned().unwrap_or(json!(0));
+                    root.insert("version".to_string(), version);
+                }
+                let state = root.entry("state").or_insert(json!({}));
+                let custom_models = lookup(state, &["customModels"]).cloned();
+                update_value(state, app, replace);
+                restore_field(object_at(state, &[]), "customModels", custom_models);
+                write_json(conn, SETTINGS_KEY, &settings)?;
+            }
+
+            if let Some(view) = value.get("view").cloned() {
+                let mut tabs = read_json(conn, TABS_KEY)?.unwrap_or(json!({}));
+                let view_settings = object_at(&mut tabs, &[]).entry("viewSettings").or_insert(json!({}));
+                let dictionary = lookup(view_settings, &["spellCheckConfig", "customDictionary"]).cloned();
+                update_value(view_settings, view, replace);
+                restore_field(object_at(view_settings, &["spellCheckConfig"]), "customDictionary", dictionary);
+                write_json(conn, TABS_KEY, &tabs)?;
+            }
+            Ok(())
+        }
+        ProfileSection::CustomModels => {
+            let mut settings = read_json(conn, SETTINGS_KEY)?.unwrap_or(json!({ "version": 0 }));
+            let state = object_at(&mut settings, &["state"]);
+            let merged = if replace { value } else { merge_by_id(state.get("customModels"), value) };
+            state.insert("customModels".to_string(), merged);
+            write_json(conn, SETTINGS_KEY, &settings)
+        }
+        ProfileSection::Dictionary => {
+            let mut tabs = read_json(conn, TABS_KEY)?.unwrap_or(json!({}));
+            let spell_check = object_at(&mut tabs, &["viewSettings", "spellCheckConfig"]);
+            let merged = if replace { value } else { merge_strings(spell_check.get("customDictionary"), value) };
+            spell_check.insert("customDictionary".to_string(), merged);
+            write_json(conn, TABS_KEY, &tabs)
+        }
+        // Only names are exported; keys have to be re-entered on the new machine
+        ProfileSection::SecretProviders => Ok(()),
+    }
+}
+
+fn read_entry<R: Read + std::io::Seek>(archive: &mut ZipArchive<R>, name: &str) -> Result<Value, String> {
+    let entry = archive
+        .by_name(name)
+        .map_err(|_| format!("Profile is missing {}", name))?;
+    let mut text = String::new();
+    entry
+        .take(MAX_ENTRY_SIZE)
+        .read_to_string(&mut text)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    serde_json::from_str(&text).map_err(|e| format!("{} is not valid JSON: {}", name, e))
+}
+
+/// Opens a profile archive and validates the manifest and every section it lists.
+fn read_profile(path: &Path) -> Result<(ProfileManifest, HashMap<ProfileSection, Value>), String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open profile: {}", e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Not a ContextPad profile: {}", e))?;
+
+    let manifest: ProfileManifest = serde_json::from_value(read_entry(&mut archive, MANIFEST_FILE)?)
+        .map_err(|e| format!("Invalid profile manifest: {}", e))?;
+    if manifest.format != PROFILE_FORMAT {
+        return Err("Not a ContextPad profile".to_string());
+    }
+    if manifest.version > PROFILE_VERSION {
+        return Err(format!(
+            "Profile format v{} is newer than this version of ContextPad supports (v{})",
+            manifest.version, PROFILE_VERSION
+        ));
+    }
+
+    let mut sections = HashMap::new();
+    for &section in &manifest.sections {
+        let value = read_entry(&mut archive, section.file_name())?;
+        validate_section(section, &value).map_err(|e| format!("Invalid {}: {}", section.file_name(), e))?;
+        sections.insert(section, value);
+    }
+
+    Ok((manifest, sections))
+}
+
+#[tauri::command]
+pub fn export_profile(
+    storage: State<'_, StorageState>,
+    path: String,
+    include_secret_providers: bool,
+) -> Result<ProfileManifest, String> {
+    let sections: Vec<ProfileSection> = ProfileSection::ALL
+        .into_iter()
+        .filter(|section| include_secret_providers || *section != ProfileSection::SecretProviders)
+        .collect();
+    let values = storage.with_conn(|conn| {
+        sections
+            .iter()
+            .map(|&section| export_section(conn, section).map(|value| (section, value)))
+            .collect::<Result<Vec<_>, String>>()
+    })?;
+
+    let manifest = ProfileManifest {
+        format: PROFILE_FORMAT.to_string(),
+        version: PROFILE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+        sections,
+    };
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    let entries = std::iter::once((MANIFEST_FILE, manifest_json)).chain(values.iter().map(|(section, value)| {
+        (section.file_name(), serde_json::to_vec_pretty(value).unwrap_or_default())
+    }));
+    for (name, bytes) in entries {
+        zip.start_file(name, options)
+            .and_then(|_| zip.write_all(&bytes).map_err(Into::into))
+            .map_err(|e| format!("Failed to write {}: {}", name, e))?;
+    }
+    let bytes = zip
+        .finish()
+        .map_err(|e| format!("Failed to finish profile: {}", e))?
+        .into_inner();
+
+    atomic_write(Path::new(&path), &bytes).map_err(|e| format!("Failed to save profile: {}", e))?;
+    Ok(manifest)
+}
+
+/// Reads and validates a profile without applying it, so the UI can offer
+/// a choice per section.
+#[tauri::command]
+pub fn inspect_profile(path: String) -> Result<ProfileManifest, String> {
+    read_profile(Path::new(&path)).map(|(manifest, _)| manifest)
+}
+
+#[derive(serde::Serialize)]
+pub struct ProfileImportResult {
+    sections: Vec<ProfileSection>,
+    // Providers the profile had keys for that this machine doesn't
+    missing_secrets: Vec<String>,
+}
+
+/// Applies the chosen sections of a profile in one transaction. Sections left
+/// out of `modes` are skipped.
+#[tauri::command]
+pub fn import_profile(
+    storage: State<'_, StorageState>,
+    path: String,
+    modes: HashMap<ProfileSection, ImportMode>,
+) -> Result<ProfileImportResult, String> {
+    let (_, mut sections) = read_profile(Path::new(&path))?;
+
+    let selected: HashSet<ProfileSection> = modes.keys().copied().collect();
+    if let Some(missing) = selected.iter().find(|section| !sections.contains_key(section)) {
+        return Err(format!("Profile has no {} section", missing.file_name().trim_end_matches(".json")));
+    }
+
+    let missing_secrets = match sections.get(&ProfileSection::SecretProviders) {
+        Some(providers) if selected.contains(&ProfileSection::SecretProviders) => providers
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|provider| provider.as_str())
+            .filter(|provider| !has_api_key(provider.to_string()).unwrap_or(false))
+            .map(String::from)
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let applied = storage.with_conn(|conn| {
+        let tx = conn.transaction()
+            .map_err(|e| format!("Failed to start import: {}", e))?;
+        let mut applied = Vec::new();
+        for section in ProfileSection::ALL {
+            let (Some(&mode), Some(value)) = (modes.get(&section), sections.remove(&section)) else { continue };
+            apply_section(&tx, section, value, mode)?;
+            applied.push(section);
+        }
+        tx.commit().map_err(|e| format!("Failed to commit import: {}", e))?;
+        Ok(applied)
+    })?;
+
+    Ok(ProfileImportResult {
+        sections: applied,
+        missing_secrets,
+    })
+}
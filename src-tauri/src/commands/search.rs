@@ -0,0 +1,429 @@
+use regex::{NoExpand, Regex, RegexBuilder};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::ipc::Channel;
+
+use super::file::{atomic_write, hash_bytes};
+use super::walk::{build_walker, WalkOptions};
+use crate::encoding;
+
+// Larger files are left to the large-file mode
+const MAX_SEARCH_FILE_SIZE: u64 = 16 * 1024 * 1024;
+const BINARY_SAMPLE_SIZE: u64 = 8 * 1024;
+const DEFAULT_MAX_MATCHES: usize = 10_000;
+const MAX_PREVIEW_CHARS: usize = 500;
+
+// Line numbers and columns are zero-based; columns count chars
+
+#[derive(serde::Deserialize, Default)]
+pub struct SearchOptions {
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    #[serde(default)]
+    pub whole_word: bool,
+}
+
+pub(crate) fn build_matcher(query: &str, options: &SearchOptions) -> Result<Regex, String> {
+    let mut pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+
+    // As in VS Code, a boundary only goes on a side that starts or ends with
+    // a word character, so `foo()` or `.env` can still match as a whole word
+    if options.whole_word {
+        let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+        let start = if is_word(query.chars().next()) { r"\b" } else { "" };
+        let end = if is_word(query.chars().next_back()) { r"\b" } else { "" };
+        pattern = format!("{}(?:{}){}", start, pattern, end);
+    }
+
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid search pattern: {}", e))
+}
+
+fn truncate_preview(line: &str) -> String {
+    line.chars().take(MAX_PREVIEW_CHARS).collect()
+}
+
+struct SearchableFile {
+    bytes: Vec<u8>,
+    decoded: encoding::DecodedText,
+}
+
+// Returns None for binary, oversized or unreadable files
+fn load_searchable(path: &Path) -> Option<SearchableFile> {
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_SEARCH_FILE_SIZE {
+        return None;
+    }
+
+    let mut sample = Vec::new();
+    fs::File::open(path).ok()?
+        .take(BINARY_SAMPLE_SIZE)
+        .read_to_end(&mut sample)
+        .ok()?;
+    if encoding::looks_binary(&sample) {
+        return None;
+    }
+
+    let bytes = fs::read(path).ok()?;
+    let decoded = encoding::decode(&bytes);
+    Some(SearchableFile { bytes, decoded })
+}
+
+fn walk_files(root: &Path, walk: &WalkOptions) -> Result<impl Iterator<Item = PathBuf>, String> {
+    Ok(build_walker(root, walk)?
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .map(|entry| entry.into_path()))
+}
+
+#[derive(serde::Serialize)]
+pub struct SearchMatch {
+    line: usize,
+    column: usize,
+    length: usize,
+    text: String,
+    before: Vec<String>,
+    after: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct FileMatches {
+    path: String,
+    matches: Vec<SearchMatch>,
+}
+
+#[derive(serde::Serialize)]
+pub struct SearchSummary {
+    files_searched: usize,
+    files_matched: usize,
+    total_matches: usize,
+    truncated: bool,
+}
+
+fn find_in_text(text: &str, matcher: &Regex, context_lines: usize, limit: usize) -> Vec<SearchMatch> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut matches = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        for found in matcher.find_iter(line) {
+            if matches.len() >= limit {
+                return matches;
+            }
+
+            let context_start = index.saturating_sub(context_lines);
+            let context_end = (index + 1 + context_lines).min(lines.len());
+
+            matches.push(SearchMatch {
+                line: index,
+                column: line[..found.start()].chars().count(),
+                length: found.as_str().chars().count(),
+                text: truncate_preview(line),
+                before: lines[context_start..index].iter().map(|l| truncate_preview(l)).collect(),
+                after: lines[index + 1..context_end].iter().map(|l| truncate_preview(l)).collect(),
+            });
+        }
+    }
+
+    matches
+}
+
+fn search(
+    root: &Path,
+    matcher: &Regex,
+    walk: &WalkOptions,
+    context_lines: usize,
+    max_matches: usize,
+    on_match: &Channel<FileMatches>,
+) -> Result<SearchSummary, String> {
+    let mut summary = SearchSummary {
+        files_searched: 0,
+        files_matched: 0,
+        total_matches: 0,
+        truncated: false,
+    };
+
+    for path in walk_files(root, walk)? {
+        if summary.total_matches >= max_matches {
+            summary.truncated = true;
+            break;
+        }
+
+        let Some(file) = load_searchable(&path) else { continue };
+        summary.files_searched += 1;
+
+        let limit = max_matches - summary.total_matches;
+        let matches = find_in_text(&file.decoded.content, matcher, context_lines, limit);
+        if matches.is_empty() {
+            continue;
+        }
+
+        summary.files_matched += 1;
+        summary.total_matches += matches.len();
+
+        on_match
+            .send(FileMatches {
+                path: path.to_string_lossy().to_string(),
+                matches,
+            })
+            .map_err(|e| format!("Failed to send matches: {}", e))?;
+    }
+
+    Ok(summary)
+}
+
+#[tauri::command]
+pub async fn search_workspace(
+    path: String,
+    query: String,
+    options: Option<SearchOptions>,
+    walk: Option<WalkOptions>,
+    context_lines: Option<usize>,
+    max_matches: Option<usize>,
+    on_match: Channel<FileMatches>,
+) -> Result<SearchSummary, String> {
+    let root = PathBuf::from(&path);
+    if !root.is_dir() {
+        return Err("Path is not a directory".to_string());
+    }
+
+    let matcher = build_matcher(&query, &options.unwrap_or_default())?;
+    let walk = walk.unwrap_or_default();
+    let context_lines = context_lines.unwrap_or(2);
+    let max_matches = max_matches.unwrap_or(DEFAULT_MAX_MATCHES);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        search(&root, &matcher, &walk, context_lines, max_matches, &on_match)
+    })
+    .await
+    .map_err(|e| format!("Search failed: {}", e))?
+}
+
+#[derive(serde::Deserialize)]
+pub struct ReplaceTarget {
+    path: String,
+    // Hash from the preview; the file is skipped if it changed since
+    hash: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct LineChange {
+    line: usize,
+    before: String,
+    after: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct FileReplacement {
+    path: String,
+    hash: String,
+    replacements: usize,
+    changes: Vec<LineChange>,
+    applied: bool,
+    error: Option<String>,
+}
+
+// Lines paired with their terminators, split wherever decoding normalizes a
+// line ending (\r\n, \n or a lone \r) so line numbers match the search
+fn split_lines_inclusive(text: &str) -> Vec<(&str, &str)> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let bytes = text.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        let ending = match bytes[index] {
+            b'\n' => 1,
+            b'\r' if bytes.get(index + 1) == Some(&b'\n') => 2,
+            b'\r' => 1,
+            _ => 0,
+        };
+        if ending == 0 {
+            index += 1;
+            continue;
+        }
+        lines.push((&text[start..index], &text[index..index + ending]));
+        index += ending;
+        start = index;
+    }
+    lines.push((&text[start..], ""));
+    lines
+}
+
+fn replace_in_file(
+    path: &Path,
+    matcher: &Regex,
+    replacement: &str,
+    expand: bool,
+    expected_hash: Option<&str>,
+    apply: bool,
+) -> Option<FileReplacement> {
+    let file = load_searchable(path)?;
+    let hash = hash_bytes(&file.bytes);
+
+    // The text as decoded, before its line endings were normalized, so each
+    // line is written back with the terminator it had
+    let (text, bom_length) = encoding::detect_encoding(&file.bytes);
+    let (text, had_errors) = text.decode_without_bom_handling(&file.bytes[bom_length..]);
+
+    let mut changes = Vec::new();
+    let mut replacements = 0;
+    let new_text: String = split_lines_inclusive(&text)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (line, ending))| {
+            let count = matcher.find_iter(line).count();
+            if count == 0 {
+                return format!("{}{}", line, ending);
+            }
+
+            let replaced = if expand {
+                matcher.replace_all(line, replacement).to_string()
+            } else {
+                matcher.replace_all(line, NoExpand(replacement)).to_string()
+            };
+
+            replacements += count;
+            changes.push(LineChange {
+                line: index,
+                before: truncate_preview(line),
+                after: truncate_preview(&replaced),
+            });
+            format!("{}{}", replaced, ending)
+        })
+        .collect();
+
+    if replacements == 0 {
+        return None;
+    }
+
+    let mut result = FileReplacement {
+        path: path.to_string_lossy().to_string(),
+        hash: hash.clone(),
+        replacements,
+        changes,
+        applied: false,
+        error: None,
+    };
+
+    // Bytes that didn't decode would be written back as U+FFFD
+    if had_errors {
+        result.error = Some(format!("File isn't valid {}; replacing would corrupt it", file.decoded.encoding.name()));
+        return Some(result);
+    }
+
+    if !apply {
+        return Some(result);
+    }
+
+    if expected_hash.is_some_and(|expected| expected != hash) {
+        result.error = Some("File changed since preview".to_string());
+        return Some(result);
+    }
+
+    // Write back in the file's own encoding and BOM
+    let written = encoding::encode(&new_text, file.decoded.encoding, file.decoded.has_bom)
+        .and_then(|bytes| {
+            atomic_write(path, &bytes).map_err(|e| format!("Failed to write file: {}", e))?;
+            Ok(hash_bytes(&bytes))
+        });
+
+    match written {
+        Ok(new_hash) => {
+            result.hash = new_hash;
+            result.applied = true;
+        }
+        Err(e) => result.error = Some(e),
+    }
+
+    Some(result)
+}
+
+/// Previews (`apply = false`) or applies a replacement across the workspace.
+/// When `targets` is given, only those files are touched; each file is written
+/// atomically and skipped if its content changed since the preview.
+#[tauri::command]
+pub async fn replace_in_workspace(
+    path: String,
+    query: String,
+    replacement: String,
+    options: Option<SearchOptions>,
+    walk: Option<WalkOptions>,
+    targets: Option<Vec<ReplaceTarget>>,
+    apply: bool,
+) -> Result<Vec<FileReplacement>, String> {
+    let root = PathBuf::from(&path);
+    if !root.is_dir() {
+        return Err("Path is not a directory".to_string());
+    }
+
+    let options = options.unwrap_or_default();
+    let matcher = build_matcher(&query, &options)?;
+    // Capture groups ($1) only make sense for regex searches
+    let expand = options.regex;
+    let walk = walk.unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let results = match targets {
+            Some(targets) => targets
+                .iter()
+                .filter(|target| Path::new(&target.path).starts_with(&root))
+                .filter_map(|target| {
+                    replace_in_file(
+                        Path::new(&target.path),
+                        &matcher,
+                        &replacement,
+                        expand,
+                        target.hash.as_deref(),
+                        apply,
+                    )
+                })
+                .collect(),
+            None => walk_files(&root, &walk)?
+                .filter_map(|file| replace_in_file(&file, &matcher, &replacement, expand, None, apply))
+                .collect(),
+        };
+        Ok(results)
+    })
+    .await
+    .map_err(|e| format!("Replace failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn whole_word(query: &str) -> Regex {
+        let options = SearchOptions { whole_word: true, ..SearchOptions::default() };
+        build_matcher(query, &options).unwrap()
+    }
+
+    #[test]
+    fn whole_word_matches_words() {
+        let matcher = whole_word("foo");
+        assert!(matcher.is_match("a foo b"));
+        assert!(!matcher.is_match("foobar"));
+        assert!(!matcher.is_match("a_foo"));
+    }
+
+    #[test]
+    fn whole_word_allows_punctuation_at_the_edges() {
+        let matcher = whole_word("foo()");
+        assert!(matcher.is_match("call foo() now"));
+        assert!(matcher.is_match("x = foo();"));
+        assert!(!matcher.is_match("callfoo()"));
+
+        let matcher = whole_word(".env");
+        assert!(matcher.is_match("load .env first"));
+        assert!(matcher.is_match(".env"));
+        assert!(!matcher.is_match(".envrc"));
+    }
+}
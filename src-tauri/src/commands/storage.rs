@@ -0,0 +1,351 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+pub const DATABASE_FILE: &str = "contextpad.db";
+
+// Each entry upgrades the schema by one version (tracked in PRAGMA user_version).
+// Never edit a shipped migration; append a new one instead.
+const MIGRATIONS: &[&str] = &[
+    // 1: key/value settings and JSON documents
+    "CREATE TABLE kv (
+        key TEXT PRIMARY KEY NOT NULL,
+        value TEXT NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+    CREATE TABLE documents (
+        collection TEXT NOT NULL,
+        id TEXT NOT NULL,
+        data TEXT NOT NULL,
+        updated_at INTEGER NOT NULL,
+        PRIMARY KEY (collection, id)
+    );",
+];
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), String> {
+    let version: i64 = conn
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(|e| format!("Failed to read schema version: {}", e))?;
+    let version = version.max(0) as usize;
+
+    if version > MIGRATIONS.len() {
+        return Err(format!(
+            "Database schema v{} is newer than this version of ContextPad supports (v{})",
+            version,
+            MIGRATIONS.len()
+        ));
+    }
+
+    for (index, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction()
+            .map_err(|e| format!("Failed to start migration: {}", e))?;
+        tx.execute_batch(sql)
+            .and_then(|_| tx.pragma_update(None, "user_version", (index + 1) as i64))
+            .and_then(|_| tx.commit())
+            .map_err(|e| format!("Migration to schema v{} failed: {}", index + 1, e))?;
+    }
+
+    Ok(())
+}
+
+/// Opens (creating if needed) and migrates the profile database. Also used by
+/// the headless CLI, which runs without a Tauri app.
+pub(crate) fn open_database(path: &Path) -> Result<Connection, String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+
+    let mut conn = Connection::open(path)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    conn.pragma_update(None, "journal_mode", "WAL")
+        .and_then(|_| conn.pragma_update(None, "synchronous", "NORMAL"))
+        .map_err(|e| format!("Failed to configure database: {}", e))?;
+    migrate(&mut conn)?;
+
+    Ok(conn)
+}
+
+pub(crate) fn get_value(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    conn.query_row("SELECT value FROM kv WHERE key = ?1", params![key], |row| row.get(0))
+        .optional()
+        .map_err(|e| format!("Failed to read {}: {}", key, e))
+}
+
+pub(crate) fn set_value(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO kv (key, value, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT (key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+        params![key, value, now_millis()],
+    )
+    .map(|_| ())
+    .map_err(|e| format!("Failed to write {}: {}", key, e))
+}
+
+fn lock_conn<T>(
+    conn: &Mutex<Option<Connection>>,
+    f: impl FnOnce(&mut Connection) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut guard = conn.lock().unwrap();
+    let conn = guard.as_mut().ok_or_else(|| "Storage unavailable".to_string())?;
+    f(conn)
+}
+
+pub struct StorageState {
+    path: PathBuf,
+    // None when the database couldn't be opened; the frontend then falls back
+    // to browser storage
+    conn: Arc<Mutex<Option<Connection>>>,
+}
+
+impl StorageState {
+    pub fn open(path: PathBuf) -> Self {
+        let conn = match open_database(&path) {
+            Ok(conn) => Some(conn),
+            Err(e) => {
+                eprintln!("Storage unavailable: {}", e);
+                None
+            }
+        };
+        Self {
+            path,
+            conn: Arc::new(Mutex::new(conn)),
+        }
+    }
+
+    pub(crate) fn with_conn<T>(&self, f: impl FnOnce(&mut Connection) -> Result<T, String>) -> Result<T, String> {
+        lock_conn(&self.conn, f)
+    }
+
+    /// `with_conn` on a worker thread, so the commands the stores call on
+    /// every change don't hold up the main thread.
+    async fn run<T, F>(&self, f: F) -> Result<T, String>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, String> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tauri::async_runtime::spawn_blocking(move || lock_conn(&conn, f))
+            .await
+            .map_err(|e| format!("Storage failed: {}", e))?
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct StorageInfo {
+    path: String,
+    schema_version: i64,
+    available: bool,
+}
+
+#[tauri::command]
+pub async fn storage_info(storage: State<'_, StorageState>) -> Result<StorageInfo, String> {
+    let schema_version = storage
+        .run(|conn| {
+            conn.pragma_query_value(None, "user_version", |row| row.get::<_, i64>(0))
+                .map_err(|e| e.to_string())
+        })
+        .await
+        .ok();
+
+    Ok(StorageInfo {
+        path: storage.path.to_string_lossy().to_string(),
+        schema_version: schema_version.unwrap_or(0),
+        available: schema_version.is_some(),
+    })
+}
+
+#[tauri::command]
+pub async fn storage_get(storage: State<'_, StorageState>, key: String) -> Result<Option<String>, String> {
+    storage.run(move |conn| get_value(conn, &key)).await
+}
+
+#[tauri::command]
+pub async fn storage_set(storage: State<'_, StorageState>, key: String, value: String) -> Result<(), String> {
+    storage.run(move |conn| set_value(conn, &key, &value)).await
+}
+
+#[tauri::command]
+pub async fn storage_remove(storage: State<'_, StorageState>, key: String) -> Result<(), String> {
+    storage.run(move |conn| {
+        conn.execute("DELETE FROM kv WHERE key = ?1", params![key])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to remove {}: {}", key, e))
+    }).await
+}
+
+/// All key/value pairs, optionally limited to keys starting with `prefix`.
+#[tauri::command]
+pub async fn storage_entries(
+    storage: State<'_, StorageState>,
+    prefix: Option<String>,
+) -> Result<HashMap<String, String>, String> {
+    storage.run(move |conn| {
+        let pattern = format!("{}%", prefix.unwrap_or_default().replace('%', "\\%").replace('_', "\\_"));
+        let mut statement = conn
+            .prepare("SELECT key, value FROM kv WHERE key LIKE ?1 ESCAPE '\\'")
+            .map_err(|e| e.to_string())?;
+        let rows = statement
+            .query_map(params![pattern], |row| Ok((row.get(0)?, row.get(1)?)))
+            .and_then(|rows| rows.collect::<Result<HashMap<String, String>, _>>())
+            .map_err(|e| format!("Failed to read storage: {}", e))?;
+        Ok(rows)
+    }).await
+}
+
+#[derive(serde::Serialize)]
+pub struct Document {
+    id: String,
+    data: serde_json::Value,
+    updated_at: i64,
+}
+
+fn to_document(id: String, data: String, updated_at: i64) -> Document {
+    Document {
+        id,
+        data: serde_json::from_str(&data).unwrap_or(serde_json::Value::Null),
+        updated_at,
+    }
+}
+
+fn put_document(conn: &Connection, collection: &str, id: &str, data: &serde_json::Value) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO documents (collection, id, data, updated_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT (collection, id) DO UPDATE SET data = excluded.data, updated_at = excluded.updated_at",
+        params![collection, id, data.to_string(), now_millis()],
+    )
+    .map(|_| ())
+    .map_err(|e| format!("Failed to write document: {}", e))
+}
+
+#[tauri::command]
+pub async fn document_put(
+    storage: State<'_, StorageState>,
+    collection: String,
+    id: String,
+    data: serde_json::Value,
+) -> Result<(), String> {
+    storage.run(move |conn| put_document(conn, &collection, &id, &data)).await
+}
+
+#[tauri::command]
+pub async fn document_get(
+    storage: State<'_, StorageState>,
+    collection: String,
+    id: String,
+) -> Result<Option<Document>, String> {
+    storage.run(move |conn| {
+        conn.query_row(
+            "SELECT id, data, updated_at FROM documents WHERE collection = ?1 AND id = ?2",
+            params![collection, id],
+            |row| Ok(to_document(row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read document: {}", e))
+    }).await
+}
+
+#[tauri::command]
+pub async fn document_list(storage: State<'_, StorageState>, collection: String) -> Result<Vec<Document>, String> {
+    storage.run(move |conn| {
+        let mut statement = conn
+            .prepare("SELECT id, data, updated_at FROM documents WHERE collection = ?1 ORDER BY id")
+            .map_err(|e| e.to_string())?;
+        let documents = statement
+            .query_map(params![collection], |row| Ok(to_document(row.get(0)?, row.get(1)?, row.get(2)?)))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to list documents: {}", e))?;
+        Ok(documents)
+    }).await
+}
+
+#[tauri::command]
+pub async fn document_delete(
+    storage: State<'_, StorageState>,
+    collection: String,
+    id: String,
+) -> Result<bool, String> {
+    storage.run(move |conn| {
+        conn.execute(
+            "DELETE FROM documents WHERE collection = ?1 AND id = ?2",
+            params![collection, id],
+        )
+        .map(|deleted| deleted > 0)
+        .map_err(|e| format!("Failed to delete document: {}", e))
+    }).await
+}
+
+#[derive(serde::Deserialize)]
+pub struct ImportedDocument {
+    collection: String,
+    id: String,
+    data: serde_json::Value,
+}
+
+#[derive(serde::Deserialize)]
+pub struct StorageImport {
+    #[serde(default)]
+    entries: HashMap<String, String>,
+    #[serde(default)]
+    documents: Vec<ImportedDocument>,
+}
+
+#[derive(serde::Serialize)]
+pub struct ImportSummary {
+    entries: usize,
+    documents: usize,
+}
+
+/// Bulk import, used to carry data over from the webview's localStorage and
+/// IndexedDB. Runs in one transaction; existing keys are kept unless
+/// `overwrite` is set.
+#[tauri::command]
+pub async fn storage_import(
+    storage: State<'_, StorageState>,
+    data: StorageImport,
+    overwrite: bool,
+) -> Result<ImportSummary, String> {
+    storage.run(move |conn| {
+        let tx = conn.transaction()
+            .map_err(|e| format!("Failed to start import: {}", e))?;
+        let mut summary = ImportSummary { entries: 0, documents: 0 };
+        let now = now_millis();
+
+        let kv_sql = if overwrite {
+            "INSERT OR REPLACE INTO kv (key, value, updated_at) VALUES (?1, ?2, ?3)"
+        } else {
+            "INSERT OR IGNORE INTO kv (key, value, updated_at) VALUES (?1, ?2, ?3)"
+        };
+        for (key, value) in &data.entries {
+            summary.entries += tx.execute(kv_sql, params![key, value, now])
+                .map_err(|e| format!("Failed to import {}: {}", key, e))?;
+        }
+
+        let document_sql = if overwrite {
+            "INSERT OR REPLACE INTO documents (collection, id, data, updated_at) VALUES (?1, ?2, ?3, ?4)"
+        } else {
+            "INSERT OR IGNORE INTO documents (collection, id, data, updated_at) VALUES (?1, ?2, ?3, ?4)"
+        };
+        for document in &data.documents {
+            summary.documents += tx
+                .execute(
+                    document_sql,
+                    params![document.collection, document.id, document.data.to_string(), now],
+                )
+                .map_err(|e| format!("Failed to import document: {}", e))?;
+        }
+
+        tx.commit().map_err(|e| format!("Failed to commit import: {}", e))?;
+        Ok(summary)
+    }).await
+}
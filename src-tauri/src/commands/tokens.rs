@@ -0,0 +1,47 @@
+use crate::tokens::{self, Encoding};
+
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct TokenCount {
+    pub tokens: usize,
+    // Token ids, only when asked for; they're mostly useful for debugging
+    pub ids: Option<Vec<u32>>,
+}
+
+fn count_text(text: &str, encoding: Encoding, include_ids: bool) -> Result<TokenCount, String> {
+    let ids = tokens::encode(text, encoding)?;
+    Ok(TokenCount {
+        tokens: ids.len(),
+        ids: include_ids.then_some(ids),
+    })
+}
+
+/// Counts the tokens of `text` on a worker thread, so encoding a large
+/// document doesn't hold up the webview.
+#[tauri::command]
+pub async fn count_tokens(
+    text: String,
+    encoding: Encoding,
+    include_ids: Option<bool>,
+) -> Result<TokenCount, String> {
+    tauri::async_runtime::spawn_blocking(move || count_text(&text, encoding, include_ids.unwrap_or(false)))
+        .await
+        .map_err(|e| format!("Token count failed: {}", e))?
+}
+
+/// Counts each of `texts` separately, e.g. a document's sections, in one call.
+#[tauri::command]
+pub async fn count_tokens_batch(
+    texts: Vec<String>,
+    encoding: Encoding,
+    include_ids: Option<bool>,
+) -> Result<Vec<TokenCount>, String> {
+    let include_ids = include_ids.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || {
+        texts
+            .iter()
+            .map(|text| count_text(text, encoding, include_ids))
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Token count failed: {}", e))?
+}
@@ -0,0 +1,164 @@
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::{DirEntry, WalkBuilder};
+use std::path::Path;
+use tauri::ipc::Channel;
+
+const BATCH_SIZE: usize = 500;
+const DEFAULT_MAX_ENTRIES: usize = 200_000;
+
+fn default_true() -> bool {
+    true
+}
+
+/// Shared traversal rules for the explorer, workspace search and file index.
+#[derive(Clone, serde::Deserialize)]
+pub struct WalkOptions {
+    #[serde(default)]
+    pub show_hidden: bool,
+    #[serde(default = "default_true")]
+    pub respect_gitignore: bool,
+    // Globs relative to the root; when present, only matching files are listed
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    pub max_depth: Option<usize>,
+    pub max_entries: Option<usize>,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            show_hidden: false,
+            respect_gitignore: true,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            max_depth: None,
+            max_entries: None,
+        }
+    }
+}
+
+// Include and exclude globs, relative to `root`
+pub(crate) fn build_overrides(root: &Path, options: &WalkOptions) -> Result<Override, String> {
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in &options.include {
+        overrides.add(glob)
+            .map_err(|e| format!("Invalid include pattern: {}", e))?;
+    }
+    for glob in &options.exclude {
+        overrides.add(&format!("!{}", glob))
+            .map_err(|e| format!("Invalid exclude pattern: {}", e))?;
+    }
+    overrides.build()
+        .map_err(|e| format!("Invalid glob patterns: {}", e))
+}
+
+pub(crate) fn build_walker(root: &Path, options: &WalkOptions) -> Result<WalkBuilder, String> {
+    build_walker_at(root, root, options)
+}
+
+/// Walks `start`, a directory inside `root`, as the walk of `root` would
+/// reach it: globs stay relative to `root` and `max_depth` counts from it.
+pub(crate) fn build_walker_at(root: &Path, start: &Path, options: &WalkOptions) -> Result<WalkBuilder, String> {
+    let overrides = build_overrides(root, options)?;
+    let start_depth = start.strip_prefix(root).map(|rest| rest.components().count()).unwrap_or(0);
+    let max_depth = options.max_depth.map(|depth| depth.saturating_sub(start_depth));
+
+    let mut builder = WalkBuilder::new(start);
+    builder
+        .hidden(!options.show_hidden)
+        .git_ignore(options.respect_gitignore)
+        .git_global(options.respect_gitignore)
+        .git_exclude(options.respect_gitignore)
+        .ignore(options.respect_gitignore)
+        // Workspaces that aren't repos still get their .gitignore honoured
+        .require_git(false)
+        .max_depth(max_depth)
+        .overrides(overrides)
+        // Repository internals are never useful to edit, even with hidden files shown
+        .filter_entry(|entry| entry.file_name() != ".git");
+
+    Ok(builder)
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct WalkEntry {
+    name: String,
+    path: String,
+    is_dir: bool,
+    depth: usize,
+}
+
+impl WalkEntry {
+    fn from_entry(entry: &DirEntry) -> Self {
+        Self {
+            name: entry.file_name().to_string_lossy().to_string(),
+            path: entry.path().to_string_lossy().to_string(),
+            is_dir: entry.file_type().map(|t| t.is_dir()).unwrap_or(false),
+            depth: entry.depth(),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct WalkSummary {
+    total: usize,
+    truncated: bool,
+}
+
+fn walk_directory(
+    root: &Path,
+    options: &WalkOptions,
+    on_batch: &Channel<Vec<WalkEntry>>,
+) -> Result<WalkSummary, String> {
+    let max_entries = options.max_entries.unwrap_or(DEFAULT_MAX_ENTRIES);
+    let mut builder = build_walker(root, options)?;
+    builder.sort_by_file_name(|a, b| a.cmp(b));
+
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    let mut total = 0;
+    let mut truncated = false;
+
+    // Depth 0 is the root itself
+    for entry in builder.build().skip(1) {
+        let Ok(entry) = entry else { continue };
+
+        if total >= max_entries {
+            truncated = true;
+            break;
+        }
+
+        batch.push(WalkEntry::from_entry(&entry));
+        total += 1;
+
+        if batch.len() >= BATCH_SIZE {
+            on_batch.send(std::mem::take(&mut batch))
+                .map_err(|e| format!("Failed to send entries: {}", e))?;
+        }
+    }
+
+    if !batch.is_empty() {
+        on_batch.send(batch)
+            .map_err(|e| format!("Failed to send entries: {}", e))?;
+    }
+
+    Ok(WalkSummary { total, truncated })
+}
+
+#[tauri::command]
+pub async fn list_directory_recursive(
+    path: String,
+    options: Option<WalkOptions>,
+    on_batch: Channel<Vec<WalkEntry>>,
+) -> Result<WalkSummary, String> {
+    let root = Path::new(&path).to_path_buf();
+    if !root.is_dir() {
+        return Err("Path is not a directory".to_string());
+    }
+    let options = options.unwrap_or_default();
+
+    tauri::async_runtime::spawn_blocking(move || walk_directory(&root, &options, &on_batch))
+        .await
+        .map_err(|e| format!("Failed to list directory: {}", e))?
+}
@@ -0,0 +1,329 @@
+use notify_debouncer_full::notify::event::{ModifyKind, RenameMode};
+use notify_debouncer_full::notify::{EventKind, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::file::{hash_file, modified_secs};
+use super::file_index::FileIndexState;
+
+// Editors often write a file in several steps (truncate, write, rename), so
+// events are collapsed over this window before being reported
+const DEBOUNCE_MS: u64 = 300;
+
+#[derive(Clone, serde::Serialize)]
+pub struct FileChangedEvent {
+    path: String,
+    is_dir: bool,
+    hash: Option<String>,
+    modified: Option<u64>,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct FileDeletedEvent {
+    path: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct FileRenamedEvent {
+    from: String,
+    to: String,
+}
+
+#[derive(Default)]
+struct WatchRegistry {
+    // Workspace folders, watched recursively (ref counted)
+    folders: HashMap<PathBuf, usize>,
+    // Individual files such as open tabs (ref counted)
+    files: HashMap<PathBuf, usize>,
+    // Workspace the file index follows; watched recursively, but its changes
+    // only reach the index, not the frontend
+    index_root: Option<PathBuf>,
+    // Last content hash seen per watched file, used to drop no-op events
+    hashes: HashMap<PathBuf, String>,
+    // Watches currently registered with the OS backend
+    active: HashMap<PathBuf, RecursiveMode>,
+}
+
+impl WatchRegistry {
+    fn is_interesting(&self, path: &Path) -> bool {
+        self.files.contains_key(path) || self.folders.keys().any(|f| path.starts_with(f))
+    }
+
+    // Files are watched through their parent directory so that editors which
+    // save by replacing the file (new inode) don't silently end the watch
+    fn desired_watches(&self) -> HashMap<PathBuf, RecursiveMode> {
+        let mut desired: HashMap<PathBuf, RecursiveMode> = self
+            .folders
+            .keys()
+            .chain(&self.index_root)
+            .map(|f| (f.clone(), RecursiveMode::Recursive))
+            .collect();
+
+        for file in self.files.keys() {
+            let Some(parent) = file.parent() else { continue };
+            if desired.iter().any(|(f, mode)| *mode == RecursiveMode::Recursive && parent.starts_with(f)) {
+                continue;
+            }
+            desired
+                .entry(parent.to_path_buf())
+                .or_insert(RecursiveMode::NonRecursive);
+        }
+
+        desired
+    }
+}
+
+type AppDebouncer = Debouncer<RecommendedWatcher, RecommendedCache>;
+
+#[derive(Default)]
+pub struct WatcherState {
+    debouncer: Mutex<Option<AppDebouncer>>,
+    registry: Arc<Mutex<WatchRegistry>>,
+}
+
+impl WatcherState {
+    /// Records the hash of content written by the app itself, so the
+    /// resulting filesystem event isn't reported as an external change.
+    pub fn record_hash(&self, path: &Path, hash: String) {
+        let mut registry = self.registry.lock().unwrap();
+        if registry.files.contains_key(path) {
+            registry.hashes.insert(path.to_path_buf(), hash);
+        }
+    }
+
+    /// Watches the indexed workspace, replacing the previous one; `None`
+    /// releases it.
+    pub fn set_index_root(&self, app: &AppHandle, root: Option<PathBuf>) -> Result<(), String> {
+        self.registry.lock().unwrap().index_root = root;
+        self.sync_watches(app)
+    }
+
+    fn sync_watches(&self, app: &AppHandle) -> Result<(), String> {
+        let mut debouncer_guard = self.debouncer.lock().unwrap();
+        if debouncer_guard.is_none() {
+            *debouncer_guard = Some(create_debouncer(app.clone(), self.registry.clone())?);
+        }
+        let debouncer = debouncer_guard.as_mut().unwrap();
+
+        let mut registry = self.registry.lock().unwrap();
+        let desired = registry.desired_watches();
+
+        let stale: Vec<PathBuf> = registry
+            .active
+            .iter()
+            .filter(|(path, mode)| desired.get(*path) != Some(*mode))
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        for path in stale {
+            // The directory may already be gone, which is fine
+            let _ = debouncer.unwatch(&path);
+            registry.active.remove(&path);
+        }
+
+        for (path, mode) in desired {
+            if registry.active.contains_key(&path) {
+                continue;
+            }
+            debouncer
+                .watch(&path, mode)
+                .map_err(|e| format!("Failed to watch path: {}", e))?;
+            registry.active.insert(path, mode);
+        }
+
+        Ok(())
+    }
+}
+
+fn create_debouncer(
+    app: AppHandle,
+    registry: Arc<Mutex<WatchRegistry>>,
+) -> Result<AppDebouncer, String> {
+    new_debouncer(
+        Duration::from_millis(DEBOUNCE_MS),
+        None,
+        move |result: DebounceEventResult| match result {
+            Ok(events) => {
+                for event in events {
+                    handle_event(&app, &registry, event.kind, &event.paths);
+                }
+            }
+            Err(errors) => {
+                for error in errors {
+                    eprintln!("File watcher error: {}", error);
+                }
+            }
+        },
+    )
+    .map_err(|e| format!("Failed to start file watcher: {}", e))
+}
+
+fn handle_event(
+    app: &AppHandle,
+    registry: &Arc<Mutex<WatchRegistry>>,
+    kind: EventKind,
+    paths: &[PathBuf],
+) {
+    let file_index = app.try_state::<FileIndexState>();
+
+    match kind {
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => {
+            let (from, to) = (&paths[0], &paths[1]);
+            if let Some(index) = &file_index {
+                index.path_renamed(from, to);
+            }
+
+            let mut registry = registry.lock().unwrap();
+            if !registry.is_interesting(from) && !registry.is_interesting(to) {
+                return;
+            }
+            // Keep following a renamed file so its tab stays in sync
+            if let Some(count) = registry.files.remove(from) {
+                registry.files.insert(to.clone(), count);
+                if let Some(hash) = registry.hashes.remove(from) {
+                    registry.hashes.insert(to.clone(), hash);
+                }
+            }
+            drop(registry);
+
+            let _ = app.emit(
+                "file-renamed",
+                FileRenamedEvent {
+                    from: from.to_string_lossy().to_string(),
+                    to: to.to_string_lossy().to_string(),
+                },
+            );
+        }
+        EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+            for path in paths {
+                if let Some(index) = &file_index {
+                    index.path_removed(path);
+                }
+
+                let mut registry = registry.lock().unwrap();
+                if !registry.is_interesting(path) {
+                    continue;
+                }
+                registry.hashes.remove(path);
+                drop(registry);
+
+                let _ = app.emit(
+                    "file-deleted",
+                    FileDeletedEvent {
+                        path: path.to_string_lossy().to_string(),
+                    },
+                );
+            }
+        }
+        EventKind::Create(_) | EventKind::Modify(_) => {
+            let created = matches!(
+                kind,
+                EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))
+            );
+
+            for path in paths {
+                // Rename-from may be reported as a modify on platforms that can't pair renames
+                if !path.exists() {
+                    if let Some(index) = &file_index {
+                        index.path_removed(path);
+                    }
+                    continue;
+                }
+
+                if created {
+                    if let Some(index) = &file_index {
+                        index.path_created(path);
+                    }
+                }
+
+                if !registry.lock().unwrap().is_interesting(path) {
+                    continue;
+                }
+
+                let is_dir = path.is_dir();
+                let hash = if is_dir { None } else { hash_file(path).ok() };
+
+                if let Some(hash) = &hash {
+                    let mut registry = registry.lock().unwrap();
+                    if registry.hashes.get(path) == Some(hash) {
+                        continue;
+                    }
+                    if registry.files.contains_key(path) {
+                        registry.hashes.insert(path.clone(), hash.clone());
+                    }
+                }
+
+                let _ = app.emit(
+                    "file-changed",
+                    FileChangedEvent {
+                        path: path.to_string_lossy().to_string(),
+                        is_dir,
+                        hash,
+                        modified: modified_secs(path).ok(),
+                    },
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+#[tauri::command]
+pub async fn watch_path(app: AppHandle, state: State<'_, WatcherState>, path: String) -> Result<(), String> {
+    let path_buf = PathBuf::from(&path);
+
+    if !path_buf.exists() {
+        return Err("Path does not exist".to_string());
+    }
+
+    if path_buf.is_dir() {
+        *state.registry.lock().unwrap().folders.entry(path_buf).or_insert(0) += 1;
+    } else {
+        // Registered before hashing, so an unwatch that arrives meanwhile
+        // finds the file and releases it
+        *state.registry.lock().unwrap().files.entry(path_buf.clone()).or_insert(0) += 1;
+
+        // Hashing a large file takes a while; keep it off the main thread
+        let to_hash = path_buf.clone();
+        let hash = tauri::async_runtime::spawn_blocking(move || hash_file(&to_hash).ok())
+            .await
+            .map_err(|e| format!("Failed to watch path: {}", e))?;
+
+        let mut registry = state.registry.lock().unwrap();
+        // Unless it was unwatched meanwhile, or a save recorded a newer hash
+        if let Some(hash) = hash.filter(|_| registry.files.contains_key(&path_buf)) {
+            registry.hashes.entry(path_buf).or_insert(hash);
+        }
+    }
+
+    state.sync_watches(&app)
+}
+
+#[tauri::command]
+pub fn unwatch_path(app: AppHandle, state: State<'_, WatcherState>, path: String) -> Result<(), String> {
+    let path_buf = PathBuf::from(&path);
+
+    {
+        let mut registry = state.registry.lock().unwrap();
+        if let Some(count) = registry.folders.get_mut(&path_buf) {
+            *count -= 1;
+            if *count == 0 {
+                registry.folders.remove(&path_buf);
+            }
+        } else if let Some(count) = registry.files.get_mut(&path_buf) {
+            *count -= 1;
+            if *count == 0 {
+                registry.files.remove(&path_buf);
+                registry.hashes.remove(&path_buf);
+            }
+        } else {
+            return Ok(());
+        }
+    }
+
+    state.sync_watches(&app)
+}
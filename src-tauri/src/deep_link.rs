@@ -0,0 +1,156 @@
+//! `contextpad://` links, as opened from a browser, wiki or script:
+//!
+//! ```text
+//! contextpad://open?path=<absolute path>[&line=<n>][&column=<n>]
+//! contextpad://new?content=<text>[&title=<title>]
+//! contextpad://template/<id>[?<variable>=<value>...]
+//! ```
+//!
+//! Values are percent-encoded (`encodeURIComponent`); a bare `+` decodes to
+//! a space.
+
+use std::collections::HashMap;
+use percent_encoding::percent_decode_str;
+use std::path::Path;
+use url::Url;
+
+use crate::cli::{OpenFile, OpenRequest};
+
+pub const SCHEME: &str = "contextpad";
+// Longer text is almost certainly not a prompt someone meant to send
+const MAX_TEXT_LEN: usize = 1024 * 1024;
+const MAX_NAME_LEN: usize = 200;
+
+#[derive(Debug, PartialEq)]
+pub enum DeepLink {
+    Open(OpenRequest),
+    New {
+        title: Option<String>,
+        content: String,
+    },
+    Template {
+        id: String,
+        variables: HashMap<String, String>,
+    },
+}
+
+// Query parameters, rejecting repeats so `?line=1&line=2` isn't silently resolved
+fn query_params(url: &Url) -> Result<HashMap<String, String>, String> {
+    let mut params = HashMap::new();
+    for (key, value) in url.query_pairs() {
+        if key.is_empty() {
+            return Err("Empty parameter name".to_string());
+        }
+        if value.len() > MAX_TEXT_LEN {
+            return Err(format!("Parameter {} is too long", key));
+        }
+        if params.insert(key.to_string(), value.to_string()).is_some() {
+            return Err(format!("Parameter {} is given more than once", key));
+        }
+    }
+    Ok(params)
+}
+
+fn reject_unknown(params: &HashMap<String, String>, known: &[&str]) -> Result<(), String> {
+    match params.keys().find(|key| !known.contains(&key.as_str())) {
+        Some(key) => Err(format!("Unknown parameter {}", key)),
+        None => Ok(()),
+    }
+}
+
+fn positive_number(params: &HashMap<String, String>, name: &str) -> Result<Option<u32>, String> {
+    match params.get(name) {
+        None => Ok(None),
+        Some(value) => match value.parse::<u32>() {
+            Ok(number) if number > 0 => Ok(Some(number)),
+            _ => Err(format!("{} must be a positive number", name)),
+        },
+    }
+}
+
+// Decoded segments after the route name, e.g. ["<id>"] for template/<id>
+fn route_segments(url: &Url) -> Vec<String> {
+    url.path_segments()
+        .map(|segments| {
+            segments
+                .filter(|segment| !segment.is_empty())
+                .map(|segment| percent_decode_str(segment).decode_utf8_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn open_route(url: &Url) -> Result<DeepLink, String> {
+    let params = query_params(url)?;
+    reject_unknown(&params, &["path", "line", "column"])?;
+
+    let path = params.get("path").ok_or("Missing path")?;
+    // There's no working directory to resolve a relative path against
+    if !Path::new(path).is_absolute() {
+        return Err("path must be absolute".to_string());
+    }
+    let line = positive_number(&params, "line")?;
+    let column = positive_number(&params, "column")?;
+    if column.is_some() && line.is_none() {
+        return Err("column needs a line".to_string());
+    }
+
+    Ok(DeepLink::Open(OpenRequest {
+        files: vec![OpenFile {
+            path: path.clone(),
+            line,
+            column,
+        }],
+        ..OpenRequest::default()
+    }))
+}
+
+fn new_route(url: &Url) -> Result<DeepLink, String> {
+    let mut params = query_params(url)?;
+    reject_unknown(&params, &["content", "title"])?;
+
+    let content = params.remove("content").ok_or("Missing content")?;
+    let title = params.remove("title").filter(|title| !title.trim().is_empty());
+    if title.as_ref().is_some_and(|title| title.len() > MAX_NAME_LEN) {
+        return Err("title is too long".to_string());
+    }
+    Ok(DeepLink::New { title, content })
+}
+
+fn template_route(url: &Url) -> Result<DeepLink, String> {
+    let segments = route_segments(url);
+    let id = match segments.as_slice() {
+        [id] => id.clone(),
+        [] => return Err("Missing template id".to_string()),
+        _ => return Err("Expected contextpad://template/<id>".to_string()),
+    };
+
+    let variables = query_params(url)?;
+    // Names end up inside {{...}} placeholders
+    let invalid = |name: &&String| {
+        name.len() > MAX_NAME_LEN || name.contains(['{', '}']) || name.trim() != name.as_str()
+    };
+    if let Some(name) = variables.keys().find(invalid) {
+        return Err(format!("Invalid variable name {}", name));
+    }
+    Ok(DeepLink::Template { id, variables })
+}
+
+/// Parses and validates a link. Errors describe what's wrong with it.
+pub fn parse(link: &str) -> Result<DeepLink, String> {
+    let url = Url::parse(link).map_err(|e| format!("Not a valid link: {}", e))?;
+    if url.scheme() != SCHEME {
+        return Err(format!("Not a {}:// link", SCHEME));
+    }
+
+    let route = url.host_str().unwrap_or_default().to_ascii_lowercase();
+    let is_bare = route_segments(&url).is_empty();
+    match route.as_str() {
+        "open" if is_bare => open_route(&url),
+        "new" if is_bare => new_route(&url),
+        "template" => template_route(&url),
+        "open" | "new" => Err(format!("Expected {}://{}?...", SCHEME, route)),
+        "" => Err("Missing action".to_string()),
+        _ => Err(format!("Unknown action {}", route)),
+    }
+}
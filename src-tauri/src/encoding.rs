@@ -0,0 +1,176 @@
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    Cr,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+pub struct DecodedText {
+    pub content: String,
+    pub encoding: &'static Encoding,
+    pub has_bom: bool,
+    pub line_ending: LineEnding,
+}
+
+// UTF-16 without a BOM shows up as text with a NUL in every other byte
+fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(4096) & !1];
+    if sample.len() < 4 {
+        return None;
+    }
+
+    let pairs = sample.len() / 2;
+    let even_nuls = sample.iter().step_by(2).filter(|b| **b == 0).count();
+    let odd_nuls = sample.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+
+    if odd_nuls * 10 >= pairs * 7 && even_nuls * 10 <= pairs {
+        Some(UTF_16LE)
+    } else if even_nuls * 10 >= pairs * 7 && odd_nuls * 10 <= pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+pub fn detect_encoding(bytes: &[u8]) -> (&'static Encoding, usize) {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(bytes) {
+        return (encoding, bom_length);
+    }
+
+    // Checked before UTF-8, since ASCII-range UTF-16 is also valid UTF-8
+    if let Some(encoding) = sniff_utf16(bytes) {
+        return (encoding, 0);
+    }
+
+    // A sequence cut off at the end is fine: callers may pass a partial sample
+    match std::str::from_utf8(bytes) {
+        Ok(_) => return (UTF_8, 0),
+        Err(e) if e.error_len().is_none() => return (UTF_8, 0),
+        Err(_) => {}
+    }
+
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    (detector.guess(None, true), 0)
+}
+
+pub fn detect_line_ending(text: &str) -> LineEnding {
+    let bytes = text.as_bytes();
+    let (mut lf, mut crlf, mut cr) = (0usize, 0usize, 0usize);
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\r' if bytes.get(i + 1) == Some(&b'\n') => {
+                crlf += 1;
+                i += 1;
+            }
+            b'\r' => cr += 1,
+            b'\n' => lf += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    if crlf > lf && crlf >= cr {
+        LineEnding::Crlf
+    } else if cr > lf && cr > crlf {
+        LineEnding::Cr
+    } else {
+        LineEnding::Lf
+    }
+}
+
+pub fn normalize_line_endings(text: &str, line_ending: LineEnding) -> String {
+    let unified = text.replace("\r\n", "\n").replace('\r', "\n");
+    match line_ending {
+        LineEnding::Lf => unified,
+        _ => unified.replace('\n', line_ending.as_str()),
+    }
+}
+
+/// Decodes raw file bytes, returning the text with `\n` line endings along
+/// with everything needed to write it back byte-compatible.
+pub fn decode(bytes: &[u8]) -> DecodedText {
+    let (encoding, bom_length) = detect_encoding(bytes);
+    let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+    let line_ending = detect_line_ending(&text);
+
+    DecodedText {
+        content: normalize_line_endings(&text, LineEnding::Lf),
+        encoding,
+        has_bom: bom_length > 0,
+        line_ending,
+    }
+}
+
+pub fn encoding_for_label(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(label.trim().as_bytes())
+        .ok_or_else(|| format!("Unknown encoding: {}", label))
+}
+
+pub fn encode(text: &str, encoding: &'static Encoding, with_bom: bool) -> Result<Vec<u8>, String> {
+    // encoding_rs only decodes UTF-16, so it is serialized by hand
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        let little_endian = encoding == UTF_16LE;
+        let mut bytes = Vec::with_capacity(text.len() * 2 + 2);
+        let units = with_bom.then_some(0xFEFF).into_iter().chain(text.encode_utf16());
+        for unit in units {
+            let pair = if little_endian { unit.to_le_bytes() } else { unit.to_be_bytes() };
+            bytes.extend_from_slice(&pair);
+        }
+        return Ok(bytes);
+    }
+
+    let (encoded, _, had_unmappable) = encoding.encode(text);
+    if had_unmappable {
+        return Err(format!(
+            "Content contains characters that cannot be saved as {}",
+            encoding.name()
+        ));
+    }
+
+    let mut bytes = Vec::with_capacity(encoded.len() + 3);
+    if with_bom && encoding == UTF_8 {
+        bytes.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+    }
+    bytes.extend_from_slice(&encoded);
+    Ok(bytes)
+}
+
+/// Heuristic binary check on a leading sample of a file: NUL bytes or a high
+/// share of control characters mean the content is not editable text.
+pub fn looks_binary(sample: &[u8]) -> bool {
+    let (encoding, bom_length) = detect_encoding(sample);
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        return false;
+    }
+
+    let body = &sample[bom_length..];
+    if body.is_empty() {
+        return false;
+    }
+    if body.contains(&0) {
+        return true;
+    }
+
+    let control = body
+        .iter()
+        .filter(|b| **b < 0x20 && !matches!(**b, b'\t' | b'\n' | b'\r' | 0x0C | 0x1B))
+        .count();
+    control * 10 > body.len()
+}
@@ -0,0 +1,670 @@
+//! The formula language of src/services/formulaParser.ts, for the headless
+//! `formula` command. Functions behave as they do in the editor; keep the two
+//! in step when adding or changing one.
+
+use base64::Engine;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Stands in for the editor's document and cursor in LINE(), GETLINE() and
+/// the other document functions.
+pub struct Document<'a> {
+    pub text: &'a str,
+    // One-based cursor position
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Document<'_> {
+    fn lines(&self) -> Vec<&str> {
+        self.text
+            .split('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .collect()
+    }
+}
+
+struct Token {
+    func: String,
+    args: Vec<String>,
+}
+
+static FUNCTION_CALL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)^([A-Z_]+)\s*\(").unwrap());
+
+// encodeURIComponent leaves these as they are
+const URI_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'!')
+    .remove(b'~')
+    .remove(b'*')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')');
+
+fn tokenize(formula: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut remaining = formula.trim().to_string();
+
+    while !remaining.is_empty() {
+        remaining = remaining.trim().to_string();
+        let Some(captures) = FUNCTION_CALL.captures(&remaining) else {
+            break;
+        };
+        let func = captures[1].to_uppercase();
+        let call_len = captures[0].len();
+        remaining = remaining[call_len..].to_string();
+
+        // Arguments up to the matching closing paren
+        let mut args = Vec::new();
+        let mut depth = 1;
+        let mut current = String::new();
+        let mut quote: Option<char> = None;
+        let mut rest = None;
+
+        for (index, ch) in remaining.char_indices() {
+            if let Some(q) = quote {
+                if ch == q {
+                    quote = None;
+                } else {
+                    current.push(ch);
+                }
+            } else if ch == '"' || ch == '\'' {
+                quote = Some(ch);
+            } else if ch == '(' {
+                depth += 1;
+                current.push(ch);
+            } else if ch == ')' {
+                depth -= 1;
+                if depth == 0 {
+                    if !current.trim().is_empty() {
+                        args.push(current.trim().to_string());
+                    }
+                    rest = Some(remaining[index + 1..].to_string());
+                    break;
+                }
+                current.push(ch);
+            } else if ch == ',' && depth == 1 {
+                args.push(current.trim().to_string());
+                current.clear();
+            } else {
+                current.push(ch);
+            }
+        }
+        if let Some(rest) = rest {
+            remaining = rest;
+        }
+
+        tokens.push(Token { func, args });
+    }
+
+    tokens
+}
+
+// parseInt(value, 10) || fallback
+fn int_or(value: Option<&str>, fallback: i64) -> i64 {
+    let Some(value) = value else { return fallback };
+    let value = value.trim_start();
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let end = digits.find(|c: char| !c.is_ascii_digit()).unwrap_or(digits.len());
+    match digits[..end].parse::<i64>() {
+        Ok(0) | Err(_) => fallback,
+        Ok(number) => sign * number,
+    }
+}
+
+// parseFloat(value): the longest leading decimal number
+fn parse_float(value: &str) -> Option<f64> {
+    static NUMBER: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^[+-]?(Infinity|(\d+\.?\d*|\.\d+)([eE][+-]?\d+)?)").unwrap());
+    NUMBER.find(value.trim_start()).and_then(|m| m.as_str().parse().ok())
+}
+
+// String(number): the shortest digits that round-trip, in exponent form from
+// 1e21 up and below 1e-6
+fn number_text(number: f64) -> String {
+    if number == 0.0 {
+        return "0".to_string();
+    } else if number.is_nan() {
+        return "NaN".to_string();
+    } else if number.is_infinite() {
+        return if number > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+
+    let sign = if number < 0.0 { "-" } else { "" };
+    let scientific = format!("{:e}", number.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let digits = mantissa.replace('.', "");
+    // The number is 0.<digits> × 10^point
+    let point = exponent.parse::<i64>().unwrap_or(0) + 1;
+    let count = digits.len() as i64;
+
+    let body = if (count..=21).contains(&point) {
+        format!("{}{}", digits, "0".repeat((point - count) as usize))
+    } else if (1..=21).contains(&point) {
+        format!("{}.{}", &digits[..point as usize], &digits[point as usize..])
+    } else if (-5..=0).contains(&point) {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    } else {
+        let exponent = point - 1;
+        let mantissa = if count == 1 { digits } else { format!("{}.{}", &digits[..1], &digits[1..]) };
+        format!("{}e{}{}", mantissa, if exponent < 0 { '-' } else { '+' }, exponent.abs())
+    };
+    format!("{}{}", sign, body)
+}
+
+// number.toFixed(decimals): ties round away from zero, judged on the exact
+// binary value, so 2.5 gives 3 but 1.005 (really 1.00499…) gives 1.00
+fn to_fixed(number: f64, decimals: usize) -> String {
+    if !number.is_finite() || number.abs() >= 1e21 {
+        return number_text(number);
+    }
+
+    // Every finite double has at most 1074 fractional digits, so this is exact
+    let exact = format!("{:.1074}", number.abs());
+    let (whole, fraction) = exact.split_once('.').unwrap_or((&exact, ""));
+    let mut digits: Vec<u8> = whole.bytes().chain(fraction.bytes().take(decimals)).collect();
+    if fraction.as_bytes().get(decimals).is_some_and(|digit| *digit >= b'5') {
+        let mut index = digits.len();
+        loop {
+            if index == 0 {
+                digits.insert(0, b'1');
+                break;
+            }
+            index -= 1;
+            if digits[index] == b'9' {
+                digits[index] = b'0';
+            } else {
+                digits[index] += 1;
+                break;
+            }
+        }
+    }
+
+    let digits = String::from_utf8(digits).unwrap_or_default();
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    let sign = if number < 0.0 { "-" } else { "" };
+    if decimals == 0 {
+        format!("{}{}", sign, whole)
+    } else {
+        format!("{}{}.{}", sign, whole, fraction)
+    }
+}
+
+// btoa: the text as Latin-1 bytes; fails on anything beyond U+00FF
+fn btoa(text: &str) -> Option<String> {
+    let bytes: Option<Vec<u8>> = text.chars().map(|c| u8::try_from(u32::from(c)).ok()).collect();
+    Some(base64::engine::general_purpose::STANDARD.encode(bytes?))
+}
+
+// atob: forgiving about whitespace and missing padding, with each decoded
+// byte read as a Latin-1 character
+fn atob(text: &str) -> Option<String> {
+    use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+    const FORGIVING: GeneralPurpose = GeneralPurpose::new(
+        &base64::alphabet::STANDARD,
+        GeneralPurposeConfig::new()
+            .with_decode_padding_mode(DecodePaddingMode::RequireNone)
+            .with_decode_allow_trailing_bits(true),
+    );
+
+    let mut data: String = text.chars().filter(|c| !matches!(c, '\t' | '\n' | '\x0c' | '\r' | ' ')).collect();
+    if data.len().is_multiple_of(4) {
+        for _ in 0..2 {
+            if data.ends_with('=') {
+                data.pop();
+            }
+        }
+    }
+    if data.len() % 4 == 1 {
+        return None;
+    }
+    let bytes = FORGIVING.decode(data).ok()?;
+    Some(bytes.into_iter().map(char::from).collect())
+}
+
+fn extract_numbers(text: &str) -> Vec<f64> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter_map(parse_float)
+        .collect()
+}
+
+// String.prototype.slice by characters, negative indices counting from the end
+fn slice(text: &str, start: i64, end: Option<i64>) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let len = chars.len() as i64;
+    let clamp = |index: i64| if index < 0 { (len + index).max(0) } else { index.min(len) };
+    let (start, end) = (clamp(start), clamp(end.unwrap_or(len)));
+    if start >= end {
+        return String::new();
+    }
+    chars[start as usize..end as usize].iter().collect()
+}
+
+fn pad(text: &str, length: i64, fill: &str, at_start: bool) -> String {
+    let missing = length - text.chars().count() as i64;
+    if missing <= 0 {
+        return text.to_string();
+    }
+    let padding: String = fill.chars().cycle().take(missing as usize).collect();
+    if at_start {
+        padding + text
+    } else {
+        text.to_string() + &padding
+    }
+}
+
+fn map_lines(text: &str, f: impl Fn(usize, &str) -> String) -> String {
+    text.split('\n')
+        .enumerate()
+        .map(|(index, line)| f(index, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn markdown_table(rows: &[Vec<String>]) -> String {
+    let header = format!("| {} |", rows[0].join(" | "));
+    let separator = format!("| {} |", vec!["---"; rows[0].len()].join(" | "));
+    let body: Vec<String> = rows[1..].iter().map(|row| format!("| {} |", row.join(" | "))).collect();
+    format!("{}\n{}\n{}", header, separator, body.join("\n"))
+}
+
+fn csv_row(line: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut previous = None;
+
+    for ch in line.chars() {
+        if ch == '"' && previous != Some('\\') {
+            in_quotes = !in_quotes;
+        } else if ch == ',' && !in_quotes {
+            cells.push(current.trim().to_string());
+            current.clear();
+        } else {
+            current.push(ch);
+        }
+        previous = Some(ch);
+    }
+    cells.push(current.trim().to_string());
+    cells
+}
+
+fn title_case(text: &str) -> String {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut previous = None;
+    text.chars()
+        .map(|ch| {
+            let starts_word = is_word(ch) && !previous.is_some_and(is_word);
+            previous = Some(ch);
+            if starts_word { ch.to_ascii_uppercase() } else { ch }
+        })
+        .collect()
+}
+
+fn case_words(text: &str, upper_first: bool) -> String {
+    static SEPARATED: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[^a-zA-Z0-9]+(.)").unwrap());
+    let lower = text.to_lowercase();
+    let joined = SEPARATED.replace_all(&lower, |c: &regex::Captures| c[1].to_uppercase());
+    let mut chars = joined.chars();
+    match chars.next() {
+        Some(first) if first != '\n' => {
+            let first = if upper_first { first.to_uppercase().to_string() } else { first.to_lowercase().to_string() };
+            first + chars.as_str()
+        }
+        _ => joined.to_string(),
+    }
+}
+
+// snake_case and kebab-case; each keeps the other's separator
+fn separated_case(text: &str, joiner: char) -> String {
+    static CAMEL_HUMP: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"([a-z])([A-Z])").unwrap());
+    static SNAKE_SEPARATORS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[\s\-]+").unwrap());
+    static KEBAB_SEPARATORS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[\s_]+").unwrap());
+
+    let joiner = joiner.to_string();
+    let split = CAMEL_HUMP.replace_all(text, format!("${{1}}{}${{2}}", joiner).as_str());
+    let separators = if joiner == "_" { &SNAKE_SEPARATORS } else { &KEBAB_SEPARATORS };
+    separators.replace_all(&split, joiner.as_str()).to_string()
+}
+
+fn call(func: &str, args: &[String], document: Option<&Document>) -> Result<String, String> {
+    let arg = |index: usize| args.get(index).map(String::as_str);
+    let text = arg(0).unwrap_or_default();
+
+    let value = match func {
+        // Text
+        "UPPER" => text.to_uppercase(),
+        "LOWER" => text.to_lowercase(),
+        "TITLE" => title_case(text),
+        "REVERSE" => text.chars().rev().collect(),
+        "TRIM" => text.trim().to_string(),
+        "LTRIM" => text.trim_start().to_string(),
+        "RTRIM" => text.trim_end().to_string(),
+
+        // Case
+        "CAMEL" => case_words(text, false),
+        "PASCAL" => case_words(text, true),
+        "SNAKE" => separated_case(text, '_').to_lowercase(),
+        "KEBAB" => separated_case(text, '-').to_lowercase(),
+        "CONSTANT" => separated_case(text, '_').to_uppercase(),
+        "SENTENCE" => {
+            let mut chars = text.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().to_string() + &chars.as_str().to_lowercase(),
+                None => String::new(),
+            }
+        }
+
+        // Insert
+        "TODAY" => chrono::Utc::now().format("%Y-%m-%d").to_string(),
+        "NOW" => chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        "TIME" => chrono::Local::now().format("%H:%M:%S").to_string(),
+        "UUID" => uuid::Uuid::new_v4().to_string(),
+        "RANDOM" => {
+            let min = int_or(arg(0), 1) as f64;
+            let max = int_or(arg(1), 100) as f64;
+            number_text((fastrand::f64() * (max - min + 1.0)).floor() + min)
+        }
+
+        // String
+        "CONCAT" => args.concat(),
+        "WRAP" => format!("{}{}{}", text, arg(1).unwrap_or_default(), arg(2).unwrap_or_default()),
+        "PREFIX" => format!("{}{}", arg(1).unwrap_or_default(), text),
+        "SUFFIX" => format!("{}{}", text, arg(1).unwrap_or_default()),
+        "REPLACE" => match arg(1) {
+            Some(search) if !text.is_empty() && !search.is_empty() => {
+                text.replace(search, arg(2).unwrap_or_default())
+            }
+            _ => text.to_string(),
+        },
+        "LEN" => text.chars().count().to_string(),
+        "LEFT" => slice(text, 0, Some(int_or(arg(1), 1))),
+        "RIGHT" => slice(text, -int_or(arg(1), 1), None),
+        "MID" => {
+            let start = int_or(arg(1), 0);
+            slice(text, start, Some(start + int_or(arg(2), 1)))
+        }
+        "REPEAT" => {
+            let count = int_or(arg(1), 2).min(100);
+            if count < 0 {
+                return Err(format!("Invalid count value: {}", count));
+            }
+            text.repeat(count as usize)
+        }
+        "PAD" | "PADSTART" => {
+            let fill = arg(2).filter(|fill| !fill.is_empty()).unwrap_or(" ");
+            pad(text, int_or(arg(1), 10), fill, func == "PADSTART")
+        }
+
+        // Encode
+        "BASE64" => btoa(text).unwrap_or_default(),
+        "BASE64D" => atob(text).unwrap_or_default(),
+        "URLENCODE" => utf8_percent_encode(text, URI_COMPONENT).to_string(),
+        "URLDECODE" => percent_decode_str(text)
+            .decode_utf8()
+            .map(|decoded| decoded.to_string())
+            .unwrap_or_else(|_| text.to_string()),
+        "HTMLESCAPE" => text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\'', "&#039;"),
+        "HTMLUNESCAPE" => text
+            .replace("&amp;", "&")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&#039;", "'"),
+
+        // Math
+        "SUM" => number_text(extract_numbers(text).iter().sum()),
+        "AVG" => {
+            let numbers = extract_numbers(text);
+            if numbers.is_empty() {
+                "0".to_string()
+            } else {
+                number_text(numbers.iter().sum::<f64>() / numbers.len() as f64)
+            }
+        }
+        "MIN" | "MAX" => {
+            let numbers = extract_numbers(text).into_iter();
+            let extreme = if func == "MIN" { numbers.reduce(f64::min) } else { numbers.reduce(f64::max) };
+            extreme.map(number_text).unwrap_or_else(|| "0".to_string())
+        }
+        "COUNT" => extract_numbers(text).len().to_string(),
+        "ROUND" => {
+            let decimals = int_or(arg(1), 0).clamp(0, 100) as usize;
+            to_fixed(parse_float(text).unwrap_or(0.0), decimals)
+        }
+        "FLOOR" => number_text(parse_float(text).unwrap_or(0.0).floor()),
+        "CEIL" => number_text(parse_float(text).unwrap_or(0.0).ceil()),
+        "ABS" => number_text(parse_float(text).unwrap_or(0.0).abs()),
+
+        // Markdown
+        "BOLD" => format!("**{}**", text),
+        "ITALIC" => format!("*{}*", text),
+        "STRIKE" => format!("~~{}~~", text),
+        "CODE" => format!("`{}`", text),
+        "CODEBLOCK" => format!("```{}\n{}\n```", arg(1).unwrap_or_default(), text),
+        "LINK" => format!("[{}]({})", text, arg(1).unwrap_or("url")),
+        "IMAGE" => format!("![{}]({})", text, arg(1).unwrap_or("url")),
+        "HEADING" => format!("{} {}", "#".repeat(int_or(arg(1), 1).clamp(1, 6) as usize), text),
+        "QUOTE" if text.is_empty() => "> ".to_string(),
+        "QUOTE" => map_lines(text, |_, line| format!("> {}", line)),
+
+        // Wrap
+        "PARENTHESES" => format!("({})", text),
+        "BRACKETS" => format!("[{}]", text),
+        "BRACES" => format!("{{{}}}", text),
+        "ANGLES" => format!("<{}>", text),
+        "SINGLEQUOTE" => format!("'{}'", text),
+        "DOUBLEQUOTE" => format!("\"{}\"", text),
+
+        // Lines
+        "SORT" | "SORTDESC" | "SORTNUMERIC" | "UNIQUE" | "JOIN" | "SPLIT" | "NUMBERLIST"
+        | "BULLETLIST" | "CHECKLIST" | "INDENT" | "UNINDENT" | "CSVTABLE" | "TSVTABLE"
+            if text.is_empty() =>
+        {
+            String::new()
+        }
+        "SORT" | "SORTDESC" => {
+            let mut lines: Vec<&str> = text.split('\n').collect();
+            lines.sort();
+            if func == "SORTDESC" {
+                lines.reverse();
+            }
+            lines.join("\n")
+        }
+        "SORTNUMERIC" => {
+            let mut lines: Vec<&str> = text.split('\n').collect();
+            lines.sort_by(|a, b| {
+                let a = parse_float(a).unwrap_or(0.0);
+                let b = parse_float(b).unwrap_or(0.0);
+                a.total_cmp(&b)
+            });
+            lines.join("\n")
+        }
+        "UNIQUE" => {
+            let mut seen = std::collections::HashSet::new();
+            let lines: Vec<&str> = text.split('\n').filter(|line| seen.insert(*line)).collect();
+            lines.join("\n")
+        }
+        "JOIN" => text.split('\n').collect::<Vec<_>>().join(arg(1).unwrap_or(" ")),
+        "SPLIT" => match arg(1).unwrap_or(",") {
+            "" => text.chars().map(String::from).collect::<Vec<_>>().join("\n"),
+            separator => text.split(separator).collect::<Vec<_>>().join("\n"),
+        },
+        "NUMBERLIST" => map_lines(text, |index, line| format!("{}. {}", index + 1, line)),
+        "BULLETLIST" => map_lines(text, |_, line| format!("- {}", line)),
+        "CHECKLIST" => map_lines(text, |_, line| format!("- [ ] {}", line)),
+        "INDENT" => {
+            let indent = " ".repeat(int_or(arg(1), 2).max(0) as usize);
+            map_lines(text, |_, line| format!("{}{}", indent, line))
+        }
+        "UNINDENT" => {
+            let count = int_or(arg(1), 2).max(1) as usize;
+            map_lines(text, |_, line| {
+                let skip: usize = line.chars().take(count).take_while(|c| c.is_whitespace()).map(char::len_utf8).sum();
+                line[skip..].to_string()
+            })
+        }
+
+        // Table
+        "CSVTABLE" => markdown_table(&text.trim().split('\n').map(csv_row).collect::<Vec<_>>()),
+        "TSVTABLE" => markdown_table(
+            &text
+                .trim()
+                .split('\n')
+                .map(|line| line.split('\t').map(|cell| cell.trim().to_string()).collect())
+                .collect::<Vec<_>>(),
+        ),
+
+        // Document
+        "LINE" => document.map_or(0, |doc| doc.line).to_string(),
+        "COL" => document.map_or(0, |doc| doc.column).to_string(),
+        "POS" => match document {
+            Some(doc) => format!("Line {}, Col {}", doc.line, doc.column),
+            None => "Line 0, Col 0".to_string(),
+        },
+        "LINECOUNT" => document.map_or(0, |doc| doc.lines().len()).to_string(),
+        "CHARCOUNT" => document.map_or(0, |doc| doc.text.chars().count()).to_string(),
+        "WORDCOUNT" => document.map_or(0, |doc| doc.text.split_whitespace().count()).to_string(),
+        "GETLINE" => match document {
+            Some(doc) => {
+                let n = int_or(arg(0), 1);
+                let lines = doc.lines();
+                if n < 1 || n as usize > lines.len() {
+                    String::new()
+                } else {
+                    lines[n as usize - 1].to_string()
+                }
+            }
+            None => String::new(),
+        },
+        "GETRANGE" => match document {
+            Some(doc) => {
+                let lines = doc.lines();
+                let count = lines.len() as i64;
+                let from = int_or(arg(0), 1);
+                let to = int_or(arg(1), from);
+                let start = from.min(count).max(1);
+                let end = to.min(count).max(start);
+                lines[start as usize - 1..end as usize].join("\n")
+            }
+            None => String::new(),
+        },
+
+        _ => return Err(format!("Unknown function: {}", func)),
+    };
+
+    Ok(value)
+}
+
+/// Runs a formula such as `UPPER(selection)`. As in the editor, `selection`
+/// stands for the selected text and empty parentheses pass it implicitly.
+pub fn execute(formula: &str, selection: &str, document: Option<&Document>) -> Result<String, String> {
+    let tokens = tokenize(formula);
+    let Some(token) = tokens.first() else {
+        return Err("Invalid formula: No function found".to_string());
+    };
+
+    let args = if token.args.is_empty() {
+        vec![selection.to_string()]
+    } else {
+        token
+            .args
+            .iter()
+            .map(|arg| {
+                if arg.trim().eq_ignore_ascii_case("selection") {
+                    Ok(selection.to_string())
+                } else if FUNCTION_CALL.is_match(arg) {
+                    execute(arg, selection, document)
+                } else {
+                    Ok(arg.clone())
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    call(&token.func, &args, document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // What executeFormula in formulaParser.ts returns for the same input
+    #[test]
+    fn matches_the_editor() {
+        let cases: &[(&str, &str, Result<&str, &str>)] = &[
+            ("UPPER()", "abc", Ok("ABC")),
+            ("TITLE(\"hello wOrld_x y\")", "", Ok("Hello WOrld_x Y")),
+            ("CAMEL('Hello big-world')", "", Ok("helloBigWorld")),
+            ("SNAKE(helloWorld foo-bar)", "", Ok("hello_world_foo_bar")),
+            ("UPPER(TRIM(selection))", "  a b  ", Ok("A B")),
+            ("REPEAT(ab, -1)", "", Err("Invalid count value: -1")),
+            ("PADSTART(7, 3, 0)", "", Ok("007")),
+            ("URLENCODE(\"a b&c/d'(x)\")", "", Ok("a%20b%26c%2Fd'(x)")),
+            ("HEADING(Title, 9)", "", Ok("###### Title")),
+            ("SUM(1, 2, 3.5)", "", Ok("1")),
+            ("SUM(\"1, 2, 3.5\")", "", Ok("6.5")),
+            ("SUM()", "0.1 0.2", Ok("0.30000000000000004")),
+            ("AVG()", "1 2", Ok("1.5")),
+            ("MIN(\"\")", "4, -2, 9", Ok("-2")),
+            ("MAX()", "1e21 5", Ok("1e+21")),
+            ("MAX()", "1e20", Ok("100000000000000000000")),
+            ("MIN()", "0.0000001 1", Ok("1e-7")),
+            ("MIN()", "0.000001 1", Ok("0.000001")),
+            ("ABS(-1.5e-7)", "", Ok("1.5e-7")),
+            ("COUNT()", "1 x 2", Ok("2")),
+            ("ROUND(2.5)", "", Ok("3")),
+            ("ROUND(-2.5)", "", Ok("-3")),
+            ("ROUND(1.005, 2)", "", Ok("1.00")),
+            ("ROUND(1.45, 1)", "", Ok("1.4")),
+            ("ROUND(9.995, 2)", "", Ok("9.99")),
+            ("ROUND(99.5)", "", Ok("100")),
+            ("ROUND(-0.001, 2)", "", Ok("-0.00")),
+            ("ROUND(3.14159, 2)", "", Ok("3.14")),
+            ("ROUND(1e21, 2)", "", Ok("1e+21")),
+            ("ROUND(abc, 2)", "", Ok("0.00")),
+            ("FLOOR(-3.5)", "", Ok("-4")),
+            ("NOPE(x)", "", Err("Unknown function: NOPE")),
+            ("hello", "", Err("Invalid formula: No function found")),
+            // Digits aren't part of a function name there either
+            ("BASE64(x)", "", Err("Invalid formula: No function found")),
+        ];
+
+        for (formula, selection, expected) in cases {
+            let result = execute(formula, selection, None);
+            assert_eq!(result.as_deref(), expected.map_err(str::to_string).as_deref(), "{}", formula);
+        }
+    }
+
+    #[test]
+    fn base64_uses_latin1_like_btoa_and_atob() {
+        assert_eq!(btoa("héllo").as_deref(), Some("aOlsbG8="));
+        assert_eq!(btoa("€"), None);
+        assert_eq!(atob("aOlsbG8").as_deref(), Some("héllo"));
+        assert_eq!(atob(" aGk=\n").as_deref(), Some("hi"));
+        assert_eq!(atob("/w==").as_deref(), Some("ÿ"));
+        assert_eq!(atob("a"), None);
+    }
+
+    #[test]
+    fn numbers_print_like_javascript() {
+        assert_eq!(number_text(2f64.powi(70)), "1.1805916207174113e+21");
+        assert_eq!(number_text(1.0 / 3.0), "0.3333333333333333");
+        assert_eq!(number_text(-1e300), "-1e+300");
+        assert_eq!(number_text(5e-324), "5e-324");
+        assert_eq!(number_text(123e-9), "1.23e-7");
+        assert_eq!(number_text(100.0), "100");
+        assert_eq!(number_text(-0.0), "0");
+    }
+}
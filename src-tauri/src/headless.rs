@@ -0,0 +1,255 @@
+//! Runs the commands of `cli::Headless` without starting the app: templates
+//! and settings are read from the same profile database the app uses, and
+//! results go to standard output.
+
+use std::collections::HashMap;
+use std::io::{IsTerminal, Read, Write};
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::cli::{Headless, HEADLESS_USAGE};
+use crate::commands::storage;
+use crate::encoding;
+use crate::formula::{self, Document};
+use crate::tokens;
+
+// Keys the frontend stores use in the database
+const TEMPLATES_KEY: &str = "contextpad-templates";
+const SETTINGS_KEY: &str = "contextpad-settings";
+
+// Placeholders the editor fills itself when inserting a template, only when
+// written exactly like this: {{ DATE }} is an ordinary variable
+const BUILTIN_VARIABLES: &[&str] = &["SELECTION", "DATE", "TIME", "DATETIME", "CURSOR"];
+
+#[derive(serde::Deserialize)]
+struct Template {
+    id: String,
+    name: String,
+    content: String,
+}
+
+/// Release builds are GUI programs on Windows and start without a console,
+/// so output would go nowhere. Borrow the one of the shell we ran from.
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // Fails harmlessly when there's no parent console or output is redirected
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+struct Profile {
+    database: PathBuf,
+}
+
+impl Profile {
+    // The directory Tauri's app_config_dir() resolves to
+    fn new(identifier: &str) -> Result<Self, String> {
+        let config_dir = dirs::config_dir().ok_or("Couldn't find the config directory")?;
+        Ok(Self {
+            database: config_dir.join(identifier).join(storage::DATABASE_FILE),
+        })
+    }
+
+    // Never creates a database; without one nothing has been saved yet
+    fn value(&self, key: &str) -> Result<Option<String>, String> {
+        if !self.database.exists() {
+            return Ok(None);
+        }
+        let conn = storage::open_database(&self.database)?;
+        storage::get_value(&conn, key)
+    }
+
+    fn templates(&self) -> Result<Vec<Template>, String> {
+        match self.value(TEMPLATES_KEY)? {
+            Some(json) => serde_json::from_str(&json).map_err(|e| format!("Failed to read templates: {}", e)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    // The model picked in the token settings, as persisted by settingsStore
+    fn selected_model(&self) -> Result<Option<String>, String> {
+        let Some(json) = self.value(SETTINGS_KEY)? else {
+            return Ok(None);
+        };
+        let settings: serde_json::Value =
+            serde_json::from_str(&json).map_err(|e| format!("Failed to read settings: {}", e))?;
+        Ok(settings
+            .pointer("/state/tokenSettings/selectedModel")
+            .and_then(|model| model.as_str())
+            .map(str::to_string))
+    }
+}
+
+fn read_stdin() -> Result<String, String> {
+    let mut bytes = Vec::new();
+    std::io::stdin()
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read standard input: {}", e))?;
+    Ok(encoding::decode(&bytes).content)
+}
+
+fn read_file(path: &str) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    Ok(encoding::decode(&bytes).content)
+}
+
+fn print(text: &str) -> Result<(), String> {
+    let mut stdout = std::io::stdout().lock();
+    let newline = if text.ends_with('\n') { "" } else { "\n" };
+    write!(stdout, "{}{}", text, newline)
+        .and_then(|_| stdout.flush())
+        .map_err(|e| format!("Failed to write output: {}", e))
+}
+
+// {{name}}, matched as extractTemplateVariables does
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{\{([^}]+)\}\}").unwrap());
+
+/// Fills a template as the editor inserts it, with the built-in placeholders
+/// filled too. Every other placeholder needs a value.
+fn render_template(template: &Template, variables: &HashMap<String, String>) -> Result<String, String> {
+    let mut names: Vec<&str> = Vec::new();
+    let mut missing: Vec<&str> = Vec::new();
+    for captures in PLACEHOLDER.captures_iter(&template.content) {
+        let raw = captures.get(1).map_or("", |m| m.as_str());
+        let name = raw.trim();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+        if !variables.contains_key(name) && !BUILTIN_VARIABLES.contains(&raw) && !missing.contains(&name) {
+            missing.push(name);
+        }
+    }
+
+    if !missing.is_empty() {
+        return Err(format!(
+            "{} needs values for {}; pass them as --var NAME=VALUE",
+            template.name,
+            missing.join(", ")
+        ));
+    }
+    let unused: Vec<&str> = variables
+        .keys()
+        .map(String::as_str)
+        .filter(|name| !names.contains(name))
+        .collect();
+    if !unused.is_empty() {
+        eprintln!("contextpad: {} doesn't use {}", template.name, unused.join(", "));
+    }
+
+    // As in processTemplateVariables: the date is UTC, the time local
+    let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let time = chrono::Local::now().format("%H:%M:%S").to_string();
+    let rendered = PLACEHOLDER.replace_all(&template.content, |captures: &regex::Captures| {
+        match variables.get(captures[1].trim()) {
+            Some(value) => value.clone(),
+            None => match &captures[1] {
+                "DATE" => date.clone(),
+                "TIME" => time.clone(),
+                "DATETIME" => format!("{} {}", date, time),
+                // No selection and no cursor outside the editor
+                _ => String::new(),
+            },
+        }
+    });
+    Ok(rendered.to_string())
+}
+
+fn render(profile: &Profile, template: &str, variables: &HashMap<String, String>) -> Result<(), String> {
+    let templates = profile.templates()?;
+    let by_name: Vec<&Template> = templates
+        .iter()
+        .filter(|t| t.name.eq_ignore_ascii_case(template))
+        .collect();
+    let found = match templates.iter().find(|t| t.id == template) {
+        Some(found) => found,
+        None => match by_name.as_slice() {
+            [found] => *found,
+            [] => return Err(format!("No template with the id or name {}", template)),
+            _ => return Err(format!("Several templates are named {}; use the id", template)),
+        },
+    };
+    print(&render_template(found, variables)?)
+}
+
+fn run_formula(formula: &str) -> Result<(), String> {
+    // Formulas like TODAY() don't need input; don't wait for it at a prompt
+    let input = if std::io::stdin().is_terminal() { String::new() } else { read_stdin()? };
+
+    // The input is the document, all of it selected, so the cursor is at its end
+    let last_line = input.rsplit('\n').next().unwrap_or_default();
+    let document = Document {
+        text: &input,
+        line: input.matches('\n').count() + 1,
+        column: last_line.chars().count() + 1,
+    };
+    print(&formula::execute(formula, &input, Some(&document))?)
+}
+
+fn count_tokens(profile: &Profile, model: Option<&str>, files: &[String]) -> Result<(), String> {
+    let model = match model {
+        Some(model) => model.to_string(),
+        // Custom models from the settings have no local tokenizer
+        None => profile
+            .selected_model()?
+            .filter(|model| tokens::model_encoding(model).is_ok())
+            .unwrap_or_else(|| tokens::DEFAULT_MODEL.to_string()),
+    };
+    let model_encoding = tokens::model_encoding(&model)?;
+    if model_encoding.approximate {
+        eprintln!("contextpad: {} is counted by its provider's API; this count is an approximation", model);
+    }
+
+    if files.is_empty() || files == ["-"] {
+        let count = tokens::count(&read_stdin()?, model_encoding.encoding)?;
+        return print(&count.to_string());
+    }
+
+    // One line per file, like wc, with a total for several
+    let mut total = 0;
+    let mut lines = Vec::new();
+    for file in files {
+        let text = if file == "-" { read_stdin()? } else { read_file(file)? };
+        let count = tokens::count(&text, model_encoding.encoding)?;
+        total += count;
+        lines.push(format!("{}\t{}", count, file));
+    }
+    if files.len() > 1 {
+        lines.push(format!("{}\ttotal", total));
+    }
+    print(&lines.join("\n"))
+}
+
+/// Runs a headless command and returns the process exit code: 0 on success,
+/// 1 when the command fails and 2 for bad arguments.
+pub fn run(command: Result<Headless, String>, identifier: &str) -> i32 {
+    attach_console();
+
+    let command = match command {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("contextpad: {}\n{}", e, HEADLESS_USAGE);
+            return 2;
+        }
+    };
+
+    let result = Profile::new(identifier).and_then(|profile| match &command {
+        Headless::Render { template, variables } => render(&profile, template, variables),
+        Headless::Formula { formula } => run_formula(formula),
+        Headless::Tokens { model, files } => count_tokens(&profile, model.as_deref(), files),
+    });
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("contextpad: {}", e);
+            1
+        }
+    }
+}
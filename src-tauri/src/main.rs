@@ -1,24 +1,39 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod cli;
 mod commands;
+mod deep_link;
+mod encoding;
+mod formula;
+mod headless;
+mod tokens;
 
-use tauri::{Manager, Emitter};
+use std::path::PathBuf;
+use tauri::Manager;
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use commands::launch;
 
 fn main() {
+  let context = tauri::generate_context!();
+  // `contextpad render|formula|tokens ...` prints a result and exits before
+  // any window, and before the single-instance plugin would forward it
+  if let Some(command) = cli::parse_headless(std::env::args().skip(1)) {
+    std::process::exit(headless::run(command, &context.config().identifier));
+  }
+
   tauri::Builder::default()
     // Single instance plugin - forwards args to existing instance
-    .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
-        let file_paths: Vec<String> = args
-            .iter()
-            .skip(1)
-            .filter(|arg| !arg.starts_with('-'))
-            .filter(|arg| std::path::Path::new(arg).exists())
-            .cloned()
-            .collect();
-
-        if !file_paths.is_empty() {
-            let _ = app.emit("open-files", file_paths);
-        }
+    .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+        // Relative paths are relative to where the second launch ran, not to us
+        let cwd = if cwd.is_empty() {
+            std::env::current_dir().unwrap_or_default()
+        } else {
+            PathBuf::from(cwd)
+        };
+        launch::queue_args(app, args.iter().skip(1), &cwd);
+        // A forwarded deep link comes through here as the launch's only argument
+        app.deep_link().handle_cli_arguments(args.iter());
 
         if let Some(window) = app.get_webview_window("main") {
             let _ = window.set_focus();
@@ -27,22 +42,34 @@ fn main() {
     }))
     // Deep link plugin
     .plugin(tauri_plugin_deep_link::init())
+    .manage(commands::watcher::WatcherState::default())
+    .manage(commands::large_file::LargeFileState::default())
+    .manage(commands::file_index::FileIndexState::default())
+    .manage(commands::launch::LaunchState::default())
     .setup(|app| {
-        let args: Vec<String> = std::env::args().collect();
-        let file_paths: Vec<String> = args
-            .iter()
-            .skip(1)
-            .filter(|arg| !arg.starts_with('-'))
-            .filter(|arg| std::path::Path::new(arg).exists())
-            .cloned()
-            .collect();
+        let data_dir = app.path().app_data_dir()?;
+        app.manage(commands::history::HistoryState::new(data_dir.join("history")));
+        app.manage(commands::journal::JournalState::new(data_dir.join("journal")));
+        let database_path = app.path().app_config_dir()?.join(commands::storage::DATABASE_FILE);
+        app.manage(commands::storage::StorageState::open(database_path));
+
+        let cwd = std::env::current_dir().unwrap_or_default();
+        launch::queue_args(app.handle(), std::env::args().skip(1), &cwd);
 
-        if !file_paths.is_empty() {
-            let app_handle = app.handle().clone();
-            std::thread::spawn(move || {
-                std::thread::sleep(std::time::Duration::from_millis(500));
-                let _ = app_handle.emit("open-files", file_paths);
-            });
+        let app_handle = app.handle().clone();
+        app.deep_link().on_open_url(move |event| {
+            for url in event.urls() {
+                launch::queue_link(&app_handle, url.as_str());
+            }
+        });
+        // Links that started the app were seen before the handler above existed
+        match app.deep_link().get_current() {
+            Ok(urls) => {
+                for url in urls.unwrap_or_default() {
+                    launch::queue_link(app.handle(), url.as_str());
+                }
+            }
+            Err(e) => eprintln!("Failed to read the launch links: {}", e),
         }
         Ok(())
     })
@@ -58,16 +85,75 @@ fn main() {
       commands::file::save_file_dialog,
       commands::file::get_file_name,
       commands::file::detect_language_from_path,
+      commands::language::detect_language,
+      commands::editorconfig::get_editor_config,
+      commands::history::record_snapshot,
+      commands::history::list_revisions,
+      commands::history::read_revision,
+      commands::history::diff_revisions,
+      commands::history::restore_revision,
+      commands::journal::journal_checkpoint,
+      commands::journal::journal_append,
+      commands::journal::journal_discard,
+      commands::journal::list_recoverable_buffers,
+      commands::storage::storage_info,
+      commands::storage::storage_get,
+      commands::storage::storage_set,
+      commands::storage::storage_remove,
+      commands::storage::storage_entries,
+      commands::storage::document_put,
+      commands::storage::document_get,
+      commands::storage::document_list,
+      commands::storage::document_delete,
+      commands::storage::storage_import,
+      commands::profile::export_profile,
+      commands::profile::inspect_profile,
+      commands::profile::import_profile,
+      commands::merge::merge_file_changes,
+      commands::diff::compute_diff,
+      commands::git::git_status,
+      commands::git::git_branch,
+      commands::git::git_changes,
+      commands::git::git_diff_file,
+      commands::git::git_stage,
+      commands::git::git_commit,
+      commands::git::git_blame,
+      commands::git::git_file_log,
+      commands::git::git_file_at_revision,
+      commands::launch::take_pending_open_requests,
+      commands::tokens::count_tokens,
+      commands::tokens::count_tokens_batch,
       commands::file::get_file_modified_time,
+      commands::file::get_file_hash,
+      commands::file::probe_file,
+      commands::file::read_file_hex,
       commands::file::open_folder_dialog,
       commands::file::read_directory,
+      commands::walk::list_directory_recursive,
+      commands::search::search_workspace,
+      commands::search::replace_in_workspace,
+      commands::file_index::index_workspace,
+      commands::file_index::clear_workspace_index,
+      commands::file_index::fuzzy_find_files,
       commands::file::rename_file,
       commands::file::open_file_explorer,
+      commands::file::create_file,
+      commands::file::create_directory,
+      commands::file::copy_path,
+      commands::file::move_path,
+      commands::file::delete_path,
+      commands::watcher::watch_path,
+      commands::watcher::unwatch_path,
+      commands::large_file::open_large_file,
+      commands::large_file::get_large_file_info,
+      commands::large_file::read_large_file_lines,
+      commands::large_file::search_large_file,
+      commands::large_file::close_large_file,
       commands::secrets::store_api_key,
       commands::secrets::get_api_key,
       commands::secrets::delete_api_key,
       commands::secrets::has_api_key,
     ])
-    .run(tauri::generate_context!())
+    .run(context)
     .expect("error while running tauri application");
 }
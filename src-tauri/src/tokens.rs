@@ -0,0 +1,74 @@
+//! Local token counting with the tiktoken encodings the frontend's model
+//! registry (src/services/tokenEstimator/models.ts) assigns to each model.
+
+use std::sync::OnceLock;
+use tiktoken_rs::CoreBPE;
+
+pub const DEFAULT_MODEL: &str = "gpt-4o";
+
+// Named as TiktokenEncoding in models.ts
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    O200kBase,
+    Cl100kBase,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModelEncoding {
+    pub encoding: Encoding,
+    // Anthropic and Google models are counted by their APIs; locally we can
+    // only approximate them, as the app does for documents too large to send
+    pub approximate: bool,
+}
+
+// Keep in step with MODEL_REGISTRY
+const MODELS: &[(&str, Encoding, bool)] = &[
+    ("gpt-4o", Encoding::O200kBase, false),
+    ("gpt-5.2", Encoding::O200kBase, false),
+    ("claude-sonnet-4-5-20250929", Encoding::O200kBase, true),
+    ("claude-opus-4-5-20251101", Encoding::O200kBase, true),
+    ("gemini-2.5-flash", Encoding::O200kBase, true),
+    ("gemini-2.5-pro", Encoding::O200kBase, true),
+];
+
+pub fn model_encoding(model: &str) -> Result<ModelEncoding, String> {
+    MODELS
+        .iter()
+        .find(|(id, _, _)| *id == model)
+        .map(|&(_, encoding, approximate)| ModelEncoding { encoding, approximate })
+        .ok_or_else(|| {
+            let known: Vec<&str> = MODELS.iter().map(|(id, _, _)| *id).collect();
+            format!("Unknown model {} (known models: {})", model, known.join(", "))
+        })
+}
+
+// Building an encoder parses its whole vocabulary, so each is built once
+fn encoder(encoding: Encoding) -> Result<&'static CoreBPE, String> {
+    static O200K: OnceLock<Result<CoreBPE, String>> = OnceLock::new();
+    static CL100K: OnceLock<Result<CoreBPE, String>> = OnceLock::new();
+
+    let cell = match encoding {
+        Encoding::O200kBase => &O200K,
+        Encoding::Cl100kBase => &CL100K,
+    };
+    cell.get_or_init(|| {
+        let built = match encoding {
+            Encoding::O200kBase => tiktoken_rs::o200k_base(),
+            Encoding::Cl100kBase => tiktoken_rs::cl100k_base(),
+        };
+        built.map_err(|e| format!("Failed to load tokenizer: {}", e))
+    })
+    .as_ref()
+    .map_err(Clone::clone)
+}
+
+/// Token ids for `text`. Special tokens such as `<|endoftext|>` are counted
+/// as the plain text they are in a document.
+pub fn encode(text: &str, encoding: Encoding) -> Result<Vec<u32>, String> {
+    Ok(encoder(encoding)?.encode_ordinary(text))
+}
+
+pub fn count(text: &str, encoding: Encoding) -> Result<usize, String> {
+    encode(text, encoding).map(|tokens| tokens.len())
+}
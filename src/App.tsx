@@ -13,7 +13,14 @@ import { useCodeBlockDetection } from './hooks/useCodeBlockDetection'
 import { useFileWatcher } from './hooks/useFileWatcher'
 import { useFileDrop } from './hooks/useFileDrop'
 import { useStartupFiles } from './hooks/useStartupFiles'
+import { useSnapshotAutosave } from './hooks/useSnapshotAutosave'
+import { useRecoveryJournal } from './hooks/useRecoveryJournal'
+import { useGitStatus } from './hooks/useGitStatus'
 import { GlobalErrorHandler } from './components/GlobalErrorHandler'
+import { MergeConflictModal } from './components/Modals/MergeConflictModal'
+import { GitCommitModal } from './components/Modals/GitCommitModal'
+import { DiffModal } from './components/Modals/DiffModal'
+import { useDiffStore } from './store/diffStore'
 import styles from './App.module.css'
 
 export default function App() {
@@ -23,6 +30,8 @@ export default function App() {
   const addTab = useTabStore(state => state.addTab)
   const tabs = useTabStore(state => state.tabs)
   const showStatusBar = useTabStore(state => state.viewSettings.showStatusBar)
+  const diffRequest = useDiffStore(state => state.request)
+  const closeDiff = useDiffStore(state => state.closeDiff)
 
   // Enable keyboard shortcuts
   useKeyboardShortcuts()
@@ -39,6 +48,15 @@ export default function App() {
   // Initialize startup files handler (Open With...)
   useStartupFiles()
 
+  // Snapshot unsaved changes into local history
+  useSnapshotAutosave()
+
+  // Mirror unsaved buffers to the backend journal and offer crash recovery
+  useRecoveryJournal()
+
+  // Refresh git status in the file tree after saves and on window focus
+  useGitStatus()
+
   // Initialize storage on mount
   useEffect(() => {
     const init = async () => {
@@ -83,6 +101,9 @@ export default function App() {
         <Sidebar />
       </div>
       {showStatusBar && <StatusBar />}
+      <MergeConflictModal />
+      <GitCommitModal />
+      {diffRequest && <DiffModal {...diffRequest} onClose={closeDiff} />}
     </Layout>
   )
 }
@@ -1,8 +1,12 @@
 import { useState, useRef, useEffect } from 'react'
-import { useTabStore } from '../../store/tabStore'
+import { useTabStore, getBackupOptions, getHistoryOptions, getSavedFormat } from '../../store/tabStore'
 import { useNotificationStore } from '../../store/notificationStore'
+import { useGitStore } from '../../store/gitStore'
 import { invoke } from '@tauri-apps/api/core'
-import { Folder, File, ChevronRight, ArrowLeft, MoreHorizontal, Save, Edit2 } from 'lucide-react'
+import { Folder, File, ChevronRight, ArrowLeft, MoreHorizontal, Save, Edit2, GitCompare, GitBranch } from 'lucide-react'
+import { FileContent, WriteResult } from '../../types/file'
+import { detectLanguage } from '../../utils/languageExtensions'
+import { DiffModal } from '../Modals/DiffModal'
 import styles from './Breadcrumb.module.css'
 
 interface FileNode {
@@ -23,6 +27,12 @@ export function Breadcrumb() {
   const [showRenameDialog, setShowRenameDialog] = useState(false)
   const [renameValue, setRenameValue] = useState('')
   const [browsePath, setBrowsePath] = useState<string>('')
+  const [compareContent, setCompareContent] = useState<string | null>(null)
+  const [branch, setBranch] = useState<string | null>(null)
+  const [showHeadDiff, setShowHeadDiff] = useState(false)
+  // Changes whenever the file tree's git status is re-read
+  const gitStatuses = useGitStore(state => state.statuses)
+  const setCommitPath = useGitStore(state => state.setCommitPath)
 
   const breadcrumbRef = useRef<HTMLDivElement>(null)
   const segmentRefs = useRef<{ [key: string]: HTMLDivElement | null }>({})
@@ -38,6 +48,20 @@ export function Breadcrumb() {
     }
   }, [activeTab?.filePath, activeTabId, openFolderPath])
 
+  // Branch of the repository holding the active file, or the open folder
+  const branchPath = activeTab?.filePath || openFolderPath
+  useEffect(() => {
+    if (!branchPath) {
+      setBranch(null)
+      return
+    }
+    let cancelled = false
+    invoke<string | null>('git_branch', { path: branchPath })
+      .then(name => { if (!cancelled) setBranch(name) })
+      .catch(() => { if (!cancelled) setBranch(null) })
+    return () => { cancelled = true }
+  }, [branchPath, gitStatuses])
+
   // Handle click outside to close dropdowns
   useEffect(() => {
     const handleClickOutside = (e: MouseEvent) => {
@@ -115,15 +139,24 @@ export function Breadcrumb() {
 
   const handleSelectFile = async (path: string) => {
     try {
-      const content = await invoke<string>('read_file', { path })
+      const file = await invoke<FileContent>('read_file', { path })
       const title = await invoke<string>('get_file_name', { path })
-      const language = await invoke<string>('detect_language_from_path', { path })
+      const language = await detectLanguage(path, file.content)
       
       const existing = tabs.find(t => t.filePath === path)
       if (existing) {
         setActiveTab(existing.id)
       } else {
-        addTab({ title, content, filePath: path, language, isDirty: false })
+        addTab({
+          title,
+          content: file.content,
+          encoding: file.encoding,
+          hasBom: file.has_bom,
+          lineEnding: file.line_ending,
+          filePath: path,
+          language,
+          isDirty: false
+        })
       }
       setOpenDropdown(null)
     } catch (err) {
@@ -165,8 +198,17 @@ export function Breadcrumb() {
   const handleSave = async () => {
     if (!activeTab?.filePath) return
     try {
-      await invoke('write_file', { path: activeTab.filePath, content: activeTab.content })
-      updateTab(activeTab.id, { isDirty: false })
+      const result = await invoke<WriteResult>('write_file', {
+        path: activeTab.filePath,
+        content: activeTab.content,
+        encoding: activeTab.encoding,
+        hasBom: activeTab.hasBom,
+        lineEnding: activeTab.lineEnding,
+        backup: getBackupOptions(),
+        applyEditorConfig: useTabStore.getState().viewSettings.applyEditorConfig,
+        history: getHistoryOptions()
+      })
+      updateTab(activeTab.id, { isDirty: false, ...getSavedFormat(result) })
     } catch (err) {
       console.error(err)
     }
@@ -198,6 +240,27 @@ export function Breadcrumb() {
         </div>
       )}
 
+      {compareContent !== null && activeTab.filePath && (
+        <DiffModal
+          title={`${activeTab.title}: saved vs. editor`}
+          oldInput={{ path: activeTab.filePath }}
+          newInput={{ text: compareContent }}
+          oldLabel={`${activeTab.title} (saved)`}
+          newLabel={`${activeTab.title} (editor)`}
+          onClose={() => setCompareContent(null)}
+        />
+      )}
+
+      {showHeadDiff && activeTab.filePath && (
+        <DiffModal
+          title={`${activeTab.title}: HEAD vs. saved`}
+          gitPath={activeTab.filePath}
+          oldLabel={`${activeTab.title} (HEAD)`}
+          newLabel={`${activeTab.title} (saved)`}
+          onClose={() => setShowHeadDiff(false)}
+        />
+      )}
+
       <div ref={breadcrumbRef} className={styles.breadcrumbBar}>
         {/* Root Workspace Segment */}
         <div 
@@ -210,6 +273,17 @@ export function Breadcrumb() {
           <ChevronRight size={14} className={styles.chevron} />
         </div>
 
+        {branch && (
+          <div
+            className={styles.branch}
+            onClick={() => setCommitPath(branchPath ?? null)}
+            title={`On branch ${branch}. Click to commit changes`}
+          >
+            <GitBranch size={12} />
+            <span>{branch}</span>
+          </div>
+        )}
+
         {/* Sub-path Segments */}
         {segments.map((name, i) => {
           const path = rootFolder + '\\' + segments.slice(0, i + 1).join('\\')
@@ -282,6 +356,16 @@ export function Breadcrumb() {
               <Save size={14} className={styles.itemIcon} />
               <span className={styles.itemText}>Save</span>
             </div>
+            <div className={styles.dropdownItem} onClick={() => { setCompareContent(activeTab.content); setOpenDropdown(null); }}>
+              <GitCompare size={14} className={styles.itemIcon} />
+              <span className={styles.itemText}>Compare with Saved</span>
+            </div>
+            {branch && (
+              <div className={styles.dropdownItem} onClick={() => { setShowHeadDiff(true); setOpenDropdown(null); }}>
+                <GitBranch size={14} className={styles.itemIcon} />
+                <span className={styles.itemText}>Compare with HEAD</span>
+              </div>
+            )}
           </div>
         )}
       </div>
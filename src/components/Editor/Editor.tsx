@@ -80,6 +80,7 @@ const autocompleteCompartment = new Compartment()
 const spellCheckCompartment = new Compartment()
 const codeLintCompartment = new Compartment()
 const markersCompartment = new Compartment()
+const readOnlyCompartment = new Compartment()
 
 export function Editor({ tabId, initialContent, onChange }: EditorProps) {
   const editorRef = useRef<HTMLDivElement>(null)
@@ -166,6 +167,7 @@ export function Editor({ tabId, initialContent, onChange }: EditorProps) {
           : []
       ),
       markersCompartment.of(codeBlockParamsExtension(viewSettings.showCodeBlockMarkers)),
+      readOnlyCompartment.of(EditorState.readOnly.of(currentTab?.readOnly ?? false)),
       slashCommandsExtension(),
       actionButtonPlugin,
       lockedEditorExtension(),
@@ -317,6 +319,16 @@ export function Editor({ tabId, initialContent, onChange }: EditorProps) {
     }
   }, [viewSettings.showCodeBlockMarkers])
 
+  // Toggle read-only mode
+  const readOnly = currentTab?.readOnly ?? false
+  useEffect(() => {
+    if (viewRef.current) {
+      viewRef.current.dispatch({
+        effects: readOnlyCompartment.reconfigure(EditorState.readOnly.of(readOnly))
+      })
+    }
+  }, [readOnly])
+
   return (
     <div className={styles.editorContainer}>
       <div ref={editorRef} className={styles.editor}></div>
@@ -2,8 +2,12 @@ import { useEffect, useState, useCallback } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import { useTabStore } from '../../store/tabStore'
 import { useNotificationStore } from '../../store/notificationStore'
+import { useGitStore } from '../../store/gitStore'
 import { VirtualizedFileTree } from './VirtualizedFileTree'
 import styles from './FileExplorer.module.css'
+import { FileContent } from '../../types/file'
+import { detectLanguage } from '../../utils/languageExtensions'
+import { getErrorMessage } from '../../utils/errorHandler'
 
 interface FileNode {
   name: string
@@ -18,10 +22,15 @@ export function FileExplorer() {
   const addTab = useTabStore(state => state.addTab)
   const addNotification = useNotificationStore(state => state.addNotification)
   const [files, setFiles] = useState<FileNode[]>([])
+  const gitBranch = useGitStore(state => state.branch)
+  const gitRoot = useGitStore(state => state.repoRoot)
+  const setCommitPath = useGitStore(state => state.setCommitPath)
 
   useEffect(() => {
+    useGitStore.getState().reset()
     if (openFolderPath) {
       loadFolder(openFolderPath)
+      useGitStore.getState().loadDirectory(openFolderPath)
     }
   }, [openFolderPath])
 
@@ -62,13 +71,16 @@ export function FileExplorer() {
 
   const handleFileClick = useCallback(async (filePath: string) => {
     try {
-      const content = await invoke<string>('read_file', { path: filePath })
+      const file = await invoke<FileContent>('read_file', { path: filePath })
       const fileName = await invoke<string>('get_file_name', { path: filePath })
-      const language = await invoke<string>('detect_language_from_path', { path: filePath })
+      const language = await detectLanguage(filePath, file.content)
 
       addTab({
         title: fileName,
-        content,
+        content: file.content,
+        encoding: file.encoding,
+        hasBom: file.has_bom,
+        lineEnding: file.line_ending,
         filePath,
         language,
         isDirty: false,
@@ -78,7 +90,7 @@ export function FileExplorer() {
       addNotification({
         type: 'error',
         message: 'Failed to open file',
-        details: String(error)
+        details: getErrorMessage(error)
       })
     }
   }, [addTab, addNotification])
@@ -109,6 +121,15 @@ export function FileExplorer() {
         <span className={styles.folderPath} title={openFolderPath}>
           {getFolderName()}
         </span>
+        {gitRoot && (
+          <button
+            className={styles.branchButton}
+            onClick={() => setCommitPath(gitRoot)}
+            title="Commit changes"
+          >
+            {gitBranch ?? 'git'}
+          </button>
+        )}
         <button className={styles.closeButton} onClick={handleCloseFolder} title="Close Folder">
           ×
         </button>
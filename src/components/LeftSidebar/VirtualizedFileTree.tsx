@@ -1,6 +1,8 @@
 import { useState, useMemo, useCallback, useRef, useEffect, memo } from 'react'
 import { FixedSizeList as List } from 'react-window'
 import { invoke } from '@tauri-apps/api/core'
+import { useGitStore } from '../../store/gitStore'
+import { GitFileStatus, GitFileState } from '../../types/file'
 import styles from './FileTreeItem.module.css'
 
 interface FileNode {
@@ -19,6 +21,7 @@ interface FlattenedNode {
   isExpanded: boolean
   isLoading: boolean
   hasChildren: boolean
+  gitStatus?: GitFileStatus
 }
 
 interface VirtualizedFileTreeProps {
@@ -28,6 +31,17 @@ interface VirtualizedFileTreeProps {
 
 const ITEM_HEIGHT = 24
 
+// Letter shown next to an entry, as in `git status --short`
+const GIT_BADGES: Record<GitFileState, string> = {
+  ignored: '',
+  untracked: 'U',
+  added: 'A',
+  renamed: 'R',
+  deleted: 'D',
+  modified: 'M',
+  conflicted: '!'
+}
+
 // Get file icon based on extension
 const getFileIcon = (name: string, isDir: boolean, isExpanded: boolean): string => {
   if (isDir) {
@@ -81,6 +95,7 @@ const Row = memo(function Row({
   }
 }) {
   const node = data.flatNodes[index]
+  const gitState = node.gitStatus?.state
 
   const handleClick = () => {
     if (!node.isDirectory) {
@@ -115,7 +130,15 @@ const Row = memo(function Row({
       <span className={styles.icon}>
         {node.isLoading ? '⏳' : getFileIcon(node.name, node.isDirectory, node.isExpanded)}
       </span>
-      <span className={styles.name}>{node.name}</span>
+      <span className={`${styles.name} ${gitState ? styles[`git-${gitState}`] : ''}`}>{node.name}</span>
+      {gitState && GIT_BADGES[gitState] && (
+        <span
+          className={`${styles.gitBadge} ${styles[`git-${gitState}`]}`}
+          title={`${gitState}${node.gitStatus?.staged ? ' (staged)' : ''}`}
+        >
+          {node.isDirectory ? '●' : GIT_BADGES[gitState]}
+        </span>
+      )}
     </div>
   )
 })
@@ -129,6 +152,8 @@ export const VirtualizedFileTree = memo(function VirtualizedFileTree({
   const [loadedChildren, setLoadedChildren] = useState<Map<string, FileNode[]>>(new Map())
   const containerRef = useRef<HTMLDivElement>(null)
   const [containerHeight, setContainerHeight] = useState(400)
+  const gitStatuses = useGitStore(state => state.statuses)
+  const loadGitStatus = useGitStore(state => state.loadDirectory)
 
   // Measure container height
   useEffect(() => {
@@ -169,7 +194,8 @@ export const VirtualizedFileTree = memo(function VirtualizedFileTree({
           isDirectory: node.is_dir,
           isExpanded,
           isLoading,
-          hasChildren: node.is_dir
+          hasChildren: node.is_dir,
+          gitStatus: gitStatuses[node.path]
         })
 
         // Only include children if expanded and loaded
@@ -181,7 +207,7 @@ export const VirtualizedFileTree = memo(function VirtualizedFileTree({
 
     flatten(files, 0)
     return result
-  }, [files, expandedDirs, loadingDirs, loadedChildren])
+  }, [files, expandedDirs, loadingDirs, loadedChildren, gitStatuses])
 
   const toggleExpand = useCallback(async (path: string) => {
     if (expandedDirs.has(path)) {
@@ -199,6 +225,7 @@ export const VirtualizedFileTree = memo(function VirtualizedFileTree({
         try {
           const children = await invoke<FileNode[]>('read_directory', { path })
           setLoadedChildren(prev => new Map(prev).set(path, children))
+          loadGitStatus(path)
         } catch (error) {
           console.error('Failed to load directory:', error)
         } finally {
@@ -212,7 +239,7 @@ export const VirtualizedFileTree = memo(function VirtualizedFileTree({
 
       setExpandedDirs(prev => new Set(prev).add(path))
     }
-  }, [expandedDirs, loadedChildren])
+  }, [expandedDirs, loadedChildren, loadGitStatus])
 
   // Memoize data object to prevent unnecessary re-renders
   const itemData = useMemo(() => ({
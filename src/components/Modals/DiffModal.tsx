@@ -0,0 +1,170 @@
+import React, { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { X, GitCompare, Copy } from 'lucide-react'
+import { DiffInput, DiffLine, DiffOptions, DiffResult } from '../../types/file'
+import { useNotificationStore } from '../../store/notificationStore'
+import styles from './DiffModal.module.css'
+
+interface DiffModalProps {
+  title: string
+  // Either two inputs for compute_diff, or a file compared with its committed version
+  oldInput?: DiffInput
+  newInput?: DiffInput
+  gitPath?: string
+  oldLabel: string
+  newLabel: string
+  onClose: () => void
+}
+
+interface Row {
+  left: DiffLine | null
+  right: DiffLine | null
+}
+
+// Deleted and inserted lines of one change sit side by side
+const toRows = (lines: DiffLine[]): Row[] => {
+  const rows: Row[] = []
+  let i = 0
+  while (i < lines.length) {
+    if (lines[i].kind === 'equal') {
+      rows.push({ left: lines[i], right: lines[i] })
+      i++
+      continue
+    }
+    const deleted: DiffLine[] = []
+    const inserted: DiffLine[] = []
+    while (i < lines.length && lines[i].kind === 'delete') deleted.push(lines[i++])
+    while (i < lines.length && lines[i].kind === 'insert') inserted.push(lines[i++])
+    for (let k = 0; k < Math.max(deleted.length, inserted.length); k++) {
+      rows.push({ left: deleted[k] ?? null, right: inserted[k] ?? null })
+    }
+  }
+  return rows
+}
+
+const LineText: React.FC<{ line: DiffLine }> = ({ line }) => {
+  if (!line.words) return <>{line.text || ' '}</>
+  return (
+    <>
+      {line.words.map((word, i) => (
+        <span key={i} className={word.kind === 'equal' ? undefined : styles.word}>{word.text}</span>
+      ))}
+    </>
+  )
+}
+
+const Cell: React.FC<{ line: DiffLine | null; side: 'left' | 'right' }> = ({ line, side }) => {
+  if (!line) return <><div className={styles.gutter} /><div className={`${styles.cell} ${styles.empty}`} /></>
+  const number = side === 'left' ? line.old_line : line.new_line
+  const kindClass = line.kind === 'delete' ? styles.deleted : line.kind === 'insert' ? styles.inserted : ''
+  return (
+    <>
+      <div className={styles.gutter}>{number !== null ? number + 1 : ''}</div>
+      <div className={`${styles.cell} ${kindClass}`}><LineText line={line} /></div>
+    </>
+  )
+}
+
+/**
+ * Side-by-side view of a compute_diff result
+ */
+export const DiffModal: React.FC<DiffModalProps> = ({ title, oldInput, newInput, gitPath, oldLabel, newLabel, onClose }) => {
+  const addNotification = useNotificationStore(state => state.addNotification)
+  const [ignoreWhitespace, setIgnoreWhitespace] = useState(false)
+  const [ignoreCase, setIgnoreCase] = useState(false)
+  const [result, setResult] = useState<DiffResult | null>(null)
+  const [error, setError] = useState<string | null>(null)
+
+  // Inputs usually arrive as fresh object literals; compare them by value
+  const inputKey = JSON.stringify([oldInput, newInput])
+
+  useEffect(() => {
+    let cancelled = false
+    const [oldSide, newSide] = JSON.parse(inputKey) as [DiffInput, DiffInput]
+    const options: DiffOptions = {
+      ignore_whitespace: ignoreWhitespace,
+      ignore_case: ignoreCase,
+      unified: true
+    }
+    // Git diffs keep their a/ and b/ paths so the patch applies with git apply
+    const request = gitPath
+      ? invoke<DiffResult>('git_diff_file', { path: gitPath, options })
+      : invoke<DiffResult>('compute_diff', {
+          old: oldSide,
+          new: newSide,
+          options: { ...options, old_label: oldLabel, new_label: newLabel }
+        })
+    request
+      .then(diff => { if (!cancelled) { setResult(diff); setError(null) } })
+      .catch(err => { if (!cancelled) setError(String(err)) })
+    return () => { cancelled = true }
+  }, [inputKey, gitPath, oldLabel, newLabel, ignoreWhitespace, ignoreCase])
+
+  const copyPatch = async () => {
+    if (!result?.unified) return
+    await navigator.clipboard.writeText(result.unified)
+    addNotification({ type: 'success', message: 'Patch copied to clipboard' })
+  }
+
+  return (
+    <div className={styles.overlay}>
+      <div className={styles.modal}>
+        <div className={styles.header}>
+          <div className={styles.title}>
+            <GitCompare size={18} />
+            <span>{title}</span>
+            {result && !result.identical && (
+              <span className={styles.stats}>
+                <span className={styles.statInsert}>+{result.insertions}</span>
+                <span className={styles.statDelete}>-{result.deletions}</span>
+              </span>
+            )}
+          </div>
+          <button className={styles.closeButton} onClick={onClose}>
+            <X size={18} />
+          </button>
+        </div>
+
+        <div className={styles.toolbar}>
+          <label className={styles.option}>
+            <input type="checkbox" checked={ignoreWhitespace} onChange={(e) => setIgnoreWhitespace(e.target.checked)} />
+            <span>Ignore Whitespace</span>
+          </label>
+          <label className={styles.option}>
+            <input type="checkbox" checked={ignoreCase} onChange={(e) => setIgnoreCase(e.target.checked)} />
+            <span>Ignore Case</span>
+          </label>
+          <div className={styles.spacer} />
+          <button className={styles.toolbarBtn} onClick={copyPatch} disabled={!result || result.identical}>
+            <Copy size={12} /> Copy Patch
+          </button>
+        </div>
+
+        <div className={styles.content}>
+          {error && <div className={styles.message}>{error}</div>}
+          {!error && !result && <div className={styles.message}>Comparing...</div>}
+          {result?.identical && <div className={styles.message}>No differences</div>}
+          {result && !result.identical && (
+            <div className={styles.grid}>
+              <div className={styles.sideHeader}>{oldLabel}</div>
+              <div className={styles.sideHeader}>{newLabel}</div>
+              {result.hunks.map((hunk, h) => (
+                <React.Fragment key={h}>
+                  <div className={styles.hunkHeader}>
+                    @@ -{hunk.old_start},{hunk.old_lines} +{hunk.new_start},{hunk.new_lines} @@
+                  </div>
+                  {toRows(hunk.lines).map((row, r) => (
+                    <React.Fragment key={r}>
+                      <Cell line={row.left} side="left" />
+                      <Cell line={row.right} side="right" />
+                    </React.Fragment>
+                  ))}
+                </React.Fragment>
+              ))}
+            </div>
+          )}
+        </div>
+      </div>
+    </div>
+  )
+}
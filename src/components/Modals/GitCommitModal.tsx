@@ -0,0 +1,199 @@
+import React, { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { X, GitCommit, GitCompare } from 'lucide-react'
+import { GitChange, GitFileState } from '../../types/file'
+import { useGitStore } from '../../store/gitStore'
+import { useTabStore } from '../../store/tabStore'
+import { useNotificationStore } from '../../store/notificationStore'
+import { DiffModal } from './DiffModal'
+import styles from './GitCommitModal.module.css'
+
+const STATE_LABELS: Record<GitFileState, string> = {
+  ignored: 'I',
+  untracked: 'U',
+  added: 'A',
+  renamed: 'R',
+  deleted: 'D',
+  modified: 'M',
+  conflicted: '!'
+}
+
+/**
+ * Lists the changes in a repository and commits the
+ * selected files with a message.
+ */
+export const GitCommitModal: React.FC = () => {
+  const commitPath = useGitStore(state => state.commitPath)
+  const setCommitPath = useGitStore(state => state.setCommitPath)
+  const refresh = useGitStore(state => state.refresh)
+  const tabs = useTabStore(state => state.tabs)
+  const addNotification = useNotificationStore(state => state.addNotification)
+
+  const [branch, setBranch] = useState<string | null>(null)
+  const [changes, setChanges] = useState<GitChange[] | null>(null)
+  const [selected, setSelected] = useState<Set<string>>(new Set())
+  const [message, setMessage] = useState('')
+  const [isCommitting, setIsCommitting] = useState(false)
+  const [diffChange, setDiffChange] = useState<GitChange | null>(null)
+
+  useEffect(() => {
+    if (!commitPath) return
+    setChanges(null)
+    invoke<string | null>('git_branch', { path: commitPath })
+      .then(setBranch)
+      .catch(() => setBranch(null))
+    invoke<GitChange[]>('git_changes', { path: commitPath })
+      .then(list => {
+        setChanges(list)
+        // Start with what is already staged, or everything when nothing is
+        const staged = list.filter(change => change.staged)
+        setSelected(new Set((staged.length > 0 ? staged : list).map(change => change.path)))
+      })
+      .catch(error => {
+        addNotification({ type: 'error', message: 'Failed to read git changes', details: String(error) })
+        setCommitPath(null)
+      })
+  }, [commitPath, addNotification, setCommitPath])
+
+  if (!commitPath) return null
+
+  const close = () => {
+    setCommitPath(null)
+    setMessage('')
+  }
+
+  const toggle = (path: string) => {
+    setSelected(prev => {
+      const next = new Set(prev)
+      if (next.has(path)) next.delete(path)
+      else next.add(path)
+      return next
+    })
+  }
+
+  const toggleAll = () => {
+    if (!changes) return
+    setSelected(selected.size === changes.length ? new Set() : new Set(changes.map(change => change.path)))
+  }
+
+  // Open tabs whose unsaved edits won't be part of the commit
+  const unsaved = tabs.filter(tab => tab.isDirty && tab.filePath && selected.has(tab.filePath))
+
+  const handleCommit = async () => {
+    setIsCommitting(true)
+    try {
+      const id = await invoke<string>('git_commit', {
+        path: commitPath,
+        files: Array.from(selected),
+        message
+      })
+      addNotification({
+        type: 'success',
+        message: `Committed ${selected.size} file${selected.size === 1 ? '' : 's'}`,
+        details: `${id.slice(0, 7)} ${message.split('\n')[0]}`
+      })
+      close()
+      refresh()
+    } catch (error) {
+      addNotification({ type: 'error', message: 'Commit failed', details: String(error) })
+    } finally {
+      setIsCommitting(false)
+    }
+  }
+
+  return (
+    <div className={styles.overlay}>
+      <div className={styles.modal}>
+        <div className={styles.header}>
+          <div className={styles.title}>
+            <GitCommit size={18} />
+            <span>Commit to {branch ?? 'repository'}</span>
+          </div>
+          <button className={styles.closeButton} onClick={close}>
+            <X size={18} />
+          </button>
+        </div>
+
+        <div className={styles.content}>
+          {changes === null && <div className={styles.hint}>Reading changes...</div>}
+          {changes?.length === 0 && <div className={styles.hint}>No changes to commit</div>}
+          {changes && changes.length > 0 && (
+            <>
+              <label className={styles.selectAll}>
+                <input
+                  type="checkbox"
+                  checked={selected.size === changes.length}
+                  onChange={toggleAll}
+                />
+                <span>{selected.size} of {changes.length} selected</span>
+              </label>
+              <div className={styles.fileList}>
+                {changes.map(change => (
+                  <div key={change.path} className={styles.fileRow}>
+                    <input
+                      type="checkbox"
+                      checked={selected.has(change.path)}
+                      onChange={() => toggle(change.path)}
+                    />
+                    <span className={`${styles.state} ${styles[change.state]}`} title={change.state}>
+                      {STATE_LABELS[change.state]}
+                    </span>
+                    <span className={styles.fileName} title={change.path}>{change.relative_path}</span>
+                    <button
+                      className={styles.diffBtn}
+                      onClick={() => setDiffChange(change)}
+                      title="Show changes"
+                    >
+                      <GitCompare size={12} />
+                    </button>
+                  </div>
+                ))}
+              </div>
+            </>
+          )}
+
+          {unsaved.length > 0 && (
+            <div className={styles.warning}>
+              Unsaved edits in {unsaved.map(tab => tab.title).join(', ')} won't be committed.
+            </div>
+          )}
+
+          <textarea
+            className={styles.message}
+            placeholder="Commit message"
+            value={message}
+            onChange={(e) => setMessage(e.target.value)}
+            onKeyDown={(e) => {
+              if (e.key === 'Enter' && (e.ctrlKey || e.metaKey) && message.trim() && selected.size > 0) {
+                handleCommit()
+              }
+            }}
+            rows={4}
+          />
+        </div>
+
+        <div className={styles.footer}>
+          <div className={styles.spacer} />
+          <button className={styles.secondaryBtn} onClick={close}>Cancel</button>
+          <button
+            className={styles.primaryBtn}
+            onClick={handleCommit}
+            disabled={isCommitting || !message.trim() || selected.size === 0}
+          >
+            {isCommitting ? 'Committing...' : 'Commit'}
+          </button>
+        </div>
+      </div>
+
+      {diffChange && (
+        <DiffModal
+          title={`${diffChange.relative_path}: HEAD vs. working tree`}
+          gitPath={diffChange.path}
+          oldLabel={`${diffChange.relative_path} (HEAD)`}
+          newLabel={`${diffChange.relative_path} (working tree)`}
+          onClose={() => setDiffChange(null)}
+        />
+      )}
+    </div>
+  )
+}
@@ -0,0 +1,113 @@
+import React, { useEffect, useState } from 'react'
+import { X, GitMerge } from 'lucide-react'
+import { useMergeStore } from '../../store/mergeStore'
+import styles from './MergeConflictModal.module.css'
+
+type Resolution = 'ours' | 'theirs' | 'both'
+
+const joinBoth = (ours: string, theirs: string) =>
+  ours && !ours.endsWith('\n') ? `${ours}\n${theirs}` : ours + theirs
+
+/**
+ * Conflict resolver shown when a dirty tab's file changed on disk and the
+ * three-way merge couldn't combine the edits on its own.
+ */
+export const MergeConflictModal: React.FC = () => {
+  const pending = useMergeStore(state => state.pending)
+  const setPending = useMergeStore(state => state.setPending)
+  const resolve = useMergeStore(state => state.resolve)
+  const [resolutions, setResolutions] = useState<Record<number, Resolution>>({})
+
+  useEffect(() => {
+    setResolutions({})
+  }, [pending])
+
+  if (!pending) return null
+
+  const conflictIndexes = pending.hunks
+    .map((hunk, index) => (hunk.type === 'conflict' ? index : -1))
+    .filter(index => index >= 0)
+  const allResolved = conflictIndexes.every(index => resolutions[index])
+
+  const resolveAll = (resolution: Resolution) => {
+    setResolutions(Object.fromEntries(conflictIndexes.map(index => [index, resolution])))
+  }
+
+  const handleApply = () => {
+    const content = pending.hunks.map((hunk, index) => {
+      if (hunk.type === 'clean') return hunk.text
+      switch (resolutions[index]) {
+        case 'theirs': return hunk.theirs
+        case 'both': return joinBoth(hunk.ours, hunk.theirs)
+        default: return hunk.ours
+      }
+    }).join('')
+    resolve(content)
+  }
+
+  return (
+    <div className={styles.overlay}>
+      <div className={styles.modal}>
+        <div className={styles.header}>
+          <div className={styles.title}>
+            <GitMerge size={18} />
+            <span>"{pending.title}" changed on disk</span>
+          </div>
+          <button className={styles.closeButton} onClick={() => setPending(null)} title="Keep my version">
+            <X size={18} />
+          </button>
+        </div>
+
+        <div className={styles.content}>
+          <p className={styles.hint}>
+            {conflictIndexes.length} conflicting change{conflictIndexes.length === 1 ? '' : 's'}.
+            Everything else was merged automatically.
+          </p>
+
+          {conflictIndexes.map((hunkIndex, i) => {
+            const hunk = pending.hunks[hunkIndex]
+            if (hunk.type !== 'conflict') return null
+            const choice = resolutions[hunkIndex]
+
+            return (
+              <div key={hunkIndex} className={styles.conflict}>
+                <div className={styles.conflictHeader}>
+                  <span>Conflict {i + 1}</span>
+                  <div className={styles.choices}>
+                    {(['ours', 'theirs', 'both'] as Resolution[]).map(resolution => (
+                      <button
+                        key={resolution}
+                        className={`${styles.choiceBtn} ${choice === resolution ? styles.active : ''}`}
+                        onClick={() => setResolutions({ ...resolutions, [hunkIndex]: resolution })}
+                      >
+                        {resolution === 'ours' ? 'Mine' : resolution === 'theirs' ? 'On Disk' : 'Both'}
+                      </button>
+                    ))}
+                  </div>
+                </div>
+                <div className={styles.sides}>
+                  <div className={styles.side}>
+                    <div className={styles.sideLabel}>Mine</div>
+                    <pre className={styles.text}>{hunk.ours || '(removed)'}</pre>
+                  </div>
+                  <div className={styles.side}>
+                    <div className={styles.sideLabel}>On Disk</div>
+                    <pre className={styles.text}>{hunk.theirs || '(removed)'}</pre>
+                  </div>
+                </div>
+              </div>
+            )
+          })}
+        </div>
+
+        <div className={styles.footer}>
+          <button className={styles.secondaryBtn} onClick={() => resolveAll('ours')}>All Mine</button>
+          <button className={styles.secondaryBtn} onClick={() => resolveAll('theirs')}>All On Disk</button>
+          <div className={styles.spacer} />
+          <button className={styles.secondaryBtn} onClick={() => setPending(null)}>Keep My Version</button>
+          <button className={styles.primaryBtn} onClick={handleApply} disabled={!allResolved}>Apply</button>
+        </div>
+      </div>
+    </div>
+  )
+}
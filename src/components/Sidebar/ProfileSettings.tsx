@@ -0,0 +1,161 @@
+import { useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { Download, Upload } from 'lucide-react'
+import { useNotificationStore } from '../../store/notificationStore'
+import { sqliteStorage } from '../../services/storage/SqliteStorage'
+import styles from './SettingsPanel.module.css'
+
+type ProfileSection =
+  | 'templates'
+  | 'actions'
+  | 'pinned_tabs'
+  | 'settings'
+  | 'custom_models'
+  | 'dictionary'
+  | 'secret_providers'
+
+type ImportChoice = 'merge' | 'replace' | 'skip'
+
+interface ProfileManifest {
+  format: string
+  version: number
+  app_version: string
+  created_at: number
+  sections: ProfileSection[]
+}
+
+interface ProfileImportResult {
+  sections: ProfileSection[]
+  missing_secrets: string[]
+}
+
+const SECTION_LABELS: Record<ProfileSection, string> = {
+  templates: 'Templates',
+  actions: 'Actions',
+  pinned_tabs: 'Pinned Workflows',
+  settings: 'Settings',
+  custom_models: 'Custom Models',
+  dictionary: 'Custom Dictionary',
+  secret_providers: 'API Key Providers'
+}
+
+export function ProfileSettings() {
+  const { addNotification } = useNotificationStore()
+  const [includeSecretProviders, setIncludeSecretProviders] = useState(false)
+  const [pending, setPending] = useState<{ path: string; manifest: ProfileManifest } | null>(null)
+  const [choices, setChoices] = useState<Partial<Record<ProfileSection, ImportChoice>>>({})
+
+  const handleExport = async () => {
+    const defaultName = `contextpad-${new Date().toISOString().split('T')[0]}.contextpad-profile`
+    const path = await invoke<string | null>('save_file_dialog', { defaultName })
+    if (!path) return
+
+    try {
+      // Settings changed in the last moment may not be written yet
+      await sqliteStorage.flush()
+      await invoke('export_profile', { path, includeSecretProviders })
+      addNotification({ type: 'success', message: 'Profile exported', details: path })
+    } catch (error) {
+      addNotification({ type: 'error', message: 'Profile export failed', details: String(error) })
+    }
+  }
+
+  const handleChooseImport = async () => {
+    const path = await invoke<string | null>('open_file_dialog')
+    if (!path) return
+
+    try {
+      const manifest = await invoke<ProfileManifest>('inspect_profile', { path })
+      setPending({ path, manifest })
+      setChoices(Object.fromEntries(manifest.sections.map(section => [section, 'merge'])))
+    } catch (error) {
+      addNotification({ type: 'error', message: 'Invalid profile', details: String(error) })
+    }
+  }
+
+  const handleImport = async () => {
+    if (!pending) return
+    const modes = Object.fromEntries(
+      Object.entries(choices).filter(([, choice]) => choice !== 'skip')
+    )
+    if (Object.keys(modes).length === 0) {
+      setPending(null)
+      return
+    }
+    if (!confirm('ContextPad will reload to apply the imported profile. Continue?')) return
+
+    // Until the reload, store changes would write the old values back
+    sqliteStorage.suspendWrites()
+    try {
+      // Let writes already on their way land before the import, not after it
+      await sqliteStorage.flush()
+      const result = await invoke<ProfileImportResult>('import_profile', { path: pending.path, modes })
+      if (result.missing_secrets.length > 0) {
+        alert(`Profile imported. Re-enter API keys for: ${result.missing_secrets.join(', ')}`)
+      }
+      // Stores keep their state in memory; reload so they pick up the import
+      window.location.reload()
+      return
+    } catch (error) {
+      sqliteStorage.resumeWrites()
+      addNotification({ type: 'error', message: 'Profile import failed', details: String(error) })
+    }
+    setPending(null)
+  }
+
+  return (
+    <>
+      <div className={styles.toggleRow}>
+        <label className={styles.toggleLabel} title="Lists which providers have API keys; the keys themselves are never exported">
+          <input
+            type="checkbox"
+            checked={includeSecretProviders}
+            onChange={(e) => setIncludeSecretProviders(e.target.checked)}
+          />
+          <span>Include API Key Providers</span>
+        </label>
+      </div>
+
+      <div className={styles.profileActions}>
+        <button className={styles.profileBtn} onClick={handleExport}>
+          <Download size={12} /> Export Profile
+        </button>
+        <button className={styles.profileBtn} onClick={handleChooseImport}>
+          <Upload size={12} /> Import Profile
+        </button>
+      </div>
+
+      {pending && (
+        <div className={styles.dictionarySection}>
+          <div className={styles.smallLabel}>
+            From ContextPad {pending.manifest.app_version}, {new Date(pending.manifest.created_at).toLocaleDateString()}
+          </div>
+          {pending.manifest.sections.map(section => (
+            <div key={section} className={styles.controlRow}>
+              <label className={styles.label}>{SECTION_LABELS[section] ?? section}</label>
+              <select
+                className={styles.select}
+                value={choices[section] ?? 'merge'}
+                onChange={(e) => setChoices({ ...choices, [section]: e.target.value as ImportChoice })}
+              >
+                {section === 'secret_providers' ? (
+                  <option value="merge">Show Missing</option>
+                ) : (
+                  <>
+                    <option value="merge">Merge</option>
+                    <option value="replace">Replace</option>
+                  </>
+                )}
+                <option value="skip">Skip</option>
+              </select>
+            </div>
+          ))}
+          <div className={styles.profileActions}>
+            <button className={styles.profileBtn} onClick={handleImport}>Apply</button>
+            <button className={styles.profileBtn} onClick={() => setPending(null)}>Cancel</button>
+          </div>
+        </div>
+      )}
+    </>
+  )
+}
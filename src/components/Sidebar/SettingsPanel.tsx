@@ -3,6 +3,7 @@ import { useTabStore } from '../../store/tabStore'
 import { THEMES } from '../../themes/themeRegistry'
 import { Plus, X, ChevronDown, ChevronRight } from 'lucide-react'
 import { TokenSettings } from './TokenSettings'
+import { ProfileSettings } from './ProfileSettings'
 import styles from './SettingsPanel.module.css'
 
 interface CollapsibleSectionProps {
@@ -50,6 +51,14 @@ export function SettingsPanel() {
     setViewSettings({ codeLintConfig: { ...viewSettings.codeLintConfig, ...updates } })
   }
 
+  const updateBackup = (updates: any) => {
+    setViewSettings({ backupConfig: { ...viewSettings.backupConfig, ...updates } })
+  }
+
+  const updateHistory = (updates: any) => {
+    setViewSettings({ historyConfig: { ...viewSettings.historyConfig, ...updates } })
+  }
+
   const addWord = () => {
     if (newWord.trim()) {
       const word = newWord.trim().toLowerCase()
@@ -362,11 +371,116 @@ export function SettingsPanel() {
         )}
       </CollapsibleSection>
 
+      {/* Saving Section */}
+      <CollapsibleSection title="Saving" defaultOpen={false}>
+        <div className={styles.toggleRow}>
+          <label className={styles.toggleLabel}>
+            <input
+              type="checkbox"
+              checked={viewSettings.applyEditorConfig ?? true}
+              onChange={(e) => setViewSettings({ applyEditorConfig: e.target.checked })}
+            />
+            <span>Apply .editorconfig on Save</span>
+          </label>
+        </div>
+
+        <div className={styles.toggleRow}>
+          <label className={styles.toggleLabel}>
+            <input
+              type="checkbox"
+              checked={viewSettings.backupConfig.enabled}
+              onChange={(e) => updateBackup({ enabled: e.target.checked })}
+            />
+            <span>Keep Backup Copies on Save</span>
+          </label>
+        </div>
+
+        {viewSettings.backupConfig.enabled && (
+          <>
+            <div className={styles.controlRow}>
+              <label className={styles.label}>Location</label>
+              <select
+                className={styles.select}
+                value={viewSettings.backupConfig.mode}
+                onChange={(e) => updateBackup({ mode: e.target.value })}
+              >
+                <option value="rotate">Next to File (.bak)</option>
+                <option value="folder">Workspace Folder</option>
+              </select>
+            </div>
+
+            <div className={styles.controlRow}>
+              <label className={styles.label}>Copies to Keep</label>
+              <input
+                type="number"
+                min={1}
+                className={styles.numberInput}
+                value={viewSettings.backupConfig.maxBackups}
+                onChange={(e) => updateBackup({ maxBackups: Math.max(1, parseInt(e.target.value) || 5) })}
+              />
+            </div>
+          </>
+        )}
+
+        <div className={styles.toggleRow}>
+          <label className={styles.toggleLabel}>
+            <input
+              type="checkbox"
+              checked={viewSettings.historyConfig.enabled}
+              onChange={(e) => updateHistory({ enabled: e.target.checked })}
+            />
+            <span>Keep Local History</span>
+          </label>
+        </div>
+
+        {viewSettings.historyConfig.enabled && (
+          <>
+            <div className={styles.controlRow}>
+              <label className={styles.label}>Revisions per File</label>
+              <input
+                type="number"
+                min={1}
+                className={styles.numberInput}
+                value={viewSettings.historyConfig.maxRevisions}
+                onChange={(e) => updateHistory({ maxRevisions: Math.max(1, parseInt(e.target.value) || 50) })}
+              />
+            </div>
+
+            <div className={styles.controlRow}>
+              <label className={styles.label}>Keep for (days, 0 = forever)</label>
+              <input
+                type="number"
+                min={0}
+                className={styles.numberInput}
+                value={viewSettings.historyConfig.maxAgeDays}
+                onChange={(e) => updateHistory({ maxAgeDays: Math.max(0, parseInt(e.target.value) || 0) })}
+              />
+            </div>
+
+            <div className={styles.controlRow}>
+              <label className={styles.label}>Autosave Snapshot (min, 0 = off)</label>
+              <input
+                type="number"
+                min={0}
+                className={styles.numberInput}
+                value={viewSettings.historyConfig.autosaveMinutes}
+                onChange={(e) => updateHistory({ autosaveMinutes: Math.max(0, parseInt(e.target.value) || 0) })}
+              />
+            </div>
+          </>
+        )}
+      </CollapsibleSection>
+
       {/* Token Calculation Section */}
       <CollapsibleSection title="Token Calculation" defaultOpen={false}>
         <TokenSettings />
       </CollapsibleSection>
 
+      {/* Profile Export/Import Section */}
+      <CollapsibleSection title="Profile" defaultOpen={false}>
+        <ProfileSettings />
+      </CollapsibleSection>
+
       {/* Large File Optimization Section */}
       <CollapsibleSection title="Large Files" defaultOpen={false}>
         <div className={styles.controlRow}>
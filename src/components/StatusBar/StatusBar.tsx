@@ -279,6 +279,16 @@ export function StatusBar() {
 
         {activeTab && (
           <>
+            {activeTab.readOnly && (
+              <span
+                className={styles.item}
+                onClick={() => useTabStore.getState().updateTab(activeTab.id, { readOnly: false })}
+                style={{ cursor: 'pointer' }}
+                title="Click to allow editing"
+              >
+                Read-only
+              </span>
+            )}
             <span className={styles.item}>
               {activeTab.language === 'markdown' ? 'Markdown' : activeTab.language.toUpperCase()}
             </span>
@@ -2,6 +2,10 @@ import { useEffect, useRef, useCallback } from 'react'
 import { getCurrentWebview } from '@tauri-apps/api/webview'
 import { invoke } from '@tauri-apps/api/core'
 import { useTabStore } from '../store/tabStore'
+import { useNotificationStore } from '../store/notificationStore'
+import { FileContent } from '../types/file'
+import { detectLanguage } from '../utils/languageExtensions'
+import { getErrorMessage } from '../utils/errorHandler'
 
 // Supported file extensions for opening
 const SUPPORTED_EXTENSIONS = new Set([
@@ -67,13 +71,16 @@ export function useFileDrop() {
         return
       }
 
-      const content = await invoke<string>('read_file', { path: filePath })
+      const file = await invoke<FileContent>('read_file', { path: filePath })
       const fileName = await invoke<string>('get_file_name', { path: filePath })
-      const language = await invoke<string>('detect_language_from_path', { path: filePath })
+      const language = await detectLanguage(filePath, file.content)
 
       addTab({
         title: fileName,
-        content,
+        content: file.content,
+        encoding: file.encoding,
+        hasBom: file.has_bom,
+        lineEnding: file.line_ending,
         filePath,
         language,
         isDirty: false
@@ -82,6 +89,11 @@ export function useFileDrop() {
       addRecentFile(filePath)
     } catch (error) {
       console.error(`Failed to open file: ${filePath}`, error)
+      useNotificationStore.getState().addNotification({
+        type: 'error',
+        message: 'Failed to open file',
+        details: getErrorMessage(error)
+      })
     }
   }, [addTab, setActiveTab, addRecentFile])
 
@@ -1,6 +1,9 @@
 import { invoke } from '@tauri-apps/api/core'
-import { useTabStore } from '../store/tabStore'
+import { useTabStore, getBackupOptions, getHistoryOptions, getSavedFormat } from '../store/tabStore'
 import { useNotificationStore } from '../store/notificationStore'
+import { FileContent, WriteResult } from '../types/file'
+import { detectLanguage } from '../utils/languageExtensions'
+import { getErrorMessage } from '../utils/errorHandler'
 
 export function useFileOperations() {
   const { addTab, updateTab, getActiveTab, addRecentFile, setOpenFolderPath, toggleLeftSidebar, showLeftSidebar } = useTabStore()
@@ -12,9 +15,9 @@ export function useFileOperations() {
 
       if (!filePath) return // User cancelled
 
-      let content = await invoke<string>('read_file', { path: filePath })
+      const file = await invoke<FileContent>('read_file', { path: filePath })
       const fileName = await invoke<string>('get_file_name', { path: filePath })
-      const language = await invoke<string>('detect_language_from_path', { path: filePath })
+      const language = await detectLanguage(filePath, file.content)
       // Extract folder path from file path
       const pathParts = filePath.split(/[\\/]/)
       pathParts.pop() // Remove filename
@@ -22,7 +25,10 @@ export function useFileOperations() {
 
       addTab({
         title: fileName,
-        content,
+        content: file.content,
+        encoding: file.encoding,
+        hasBom: file.has_bom,
+        lineEnding: file.line_ending,
         filePath,
         folderPath,
         isDirty: false,
@@ -42,7 +48,7 @@ export function useFileOperations() {
       addNotification({
         type: 'error',
         message: 'Failed to open file',
-        details: String(error)
+        details: getErrorMessage(error)
       })
     }
   }
@@ -63,16 +69,26 @@ export function useFileOperations() {
         if (!filePath) return // User cancelled
       }
 
-      await invoke('write_file', { path: filePath, content: tab.content })
+      const result = await invoke<WriteResult>('write_file', {
+        path: filePath,
+        content: tab.content,
+        encoding: tab.encoding,
+        hasBom: tab.hasBom,
+        lineEnding: tab.lineEnding,
+        backup: getBackupOptions(),
+        applyEditorConfig: useTabStore.getState().viewSettings.applyEditorConfig,
+        history: getHistoryOptions()
+      })
 
       const fileName = await invoke<string>('get_file_name', { path: filePath })
-      const language = await invoke<string>('detect_language_from_path', { path: filePath })
+      const language = await detectLanguage(filePath, tab.content)
 
       updateTab(tab.id, {
         title: fileName,
         filePath,
         language,
         isDirty: false,
+        ...getSavedFormat(result)
       })
 
       // Add to recent files
@@ -108,16 +124,25 @@ export function useFileOperations() {
 
       if (!filePath) return // User cancelled
 
-      await invoke('write_file', { path: filePath, content: tab.content })
+      const result = await invoke<WriteResult>('write_file', {
+        path: filePath,
+        content: tab.content,
+        encoding: tab.encoding,
+        hasBom: tab.hasBom,
+        lineEnding: tab.lineEnding,
+        applyEditorConfig: useTabStore.getState().viewSettings.applyEditorConfig,
+        history: getHistoryOptions()
+      })
 
       const fileName = await invoke<string>('get_file_name', { path: filePath })
-      const language = await invoke<string>('detect_language_from_path', { path: filePath })
+      const language = await detectLanguage(filePath, tab.content)
 
       updateTab(tab.id, {
         title: fileName,
         filePath,
         language,
         isDirty: false,
+        ...getSavedFormat(result)
       })
 
       // Add to recent files
@@ -150,13 +175,16 @@ export function useFileOperations() {
 
   const openRecentFile = async (filePath: string) => {
     try {
-      let content = await invoke<string>('read_file', { path: filePath })
+      const file = await invoke<FileContent>('read_file', { path: filePath })
       const fileName = await invoke<string>('get_file_name', { path: filePath })
-      const language = await invoke<string>('detect_language_from_path', { path: filePath })
+      const language = await detectLanguage(filePath, file.content)
 
       addTab({
         title: fileName,
-        content,
+        content: file.content,
+        encoding: file.encoding,
+        hasBom: file.has_bom,
+        lineEnding: file.line_ending,
         filePath,
         language,
         isDirty: false,
@@ -169,7 +197,7 @@ export function useFileOperations() {
       addNotification({
         type: 'error',
         message: 'Failed to open recent file',
-        details: String(error)
+        details: getErrorMessage(error)
       })
     }
   }
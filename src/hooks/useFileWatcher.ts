@@ -1,96 +1,198 @@
-import { useEffect, useRef, useCallback } from 'react'
+import { useEffect, useMemo, useRef } from 'react'
 import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
 import { useTabStore } from '../store/tabStore'
 import { useNotificationStore } from '../store/notificationStore'
+import { useMergeStore, getMergeBase, setMergeBase } from '../store/mergeStore'
+import { MergeResult } from '../types/file'
+
+interface FileChangedPayload {
+  path: string
+  is_dir: boolean
+  hash: string | null
+  modified: number | null
+}
+
+interface FileDeletedPayload {
+  path: string
+}
+
+interface FileRenamedPayload {
+  from: string
+  to: string
+}
 
 /**
  * Hook to watch for external file changes
- * OPTIMIZED: Only checks active tab, with longer interval and debouncing
+ * Uses the native watcher (watch_path / unwatch_path) for every open tab
+ * and the open workspace folder. Events are debounced on the Rust side.
+ * Dirty tabs are three-way merged with the new disk content; conflicts open
+ * the merge resolver instead of leaving the next save to clobber them.
  */
 export function useFileWatcher() {
   const tabs = useTabStore(state => state.tabs)
-  const activeTabId = useTabStore(state => state.activeTabId)
+  const openFolderPath = useTabStore(state => state.openFolderPath)
   const updateTab = useTabStore(state => state.updateTab)
   const addNotification = useNotificationStore(state => state.addNotification)
 
-  // Track last checked time and modified time per tab
-  const lastCheckedRef = useRef<Map<string, number>>(new Map())
-  const isCheckingRef = useRef(false)
+  // Paths currently registered with the backend watcher
+  const watchedRef = useRef<Set<string>>(new Set())
 
-  // Get active tab
-  const activeTab = tabs.find(t => t.id === activeTabId)
+  // Only re-sync when the set of paths changes, not on every content edit
+  const watchKey = useMemo(() => {
+    const paths = tabs.map(t => t.filePath).filter((p): p is string => !!p)
+    if (openFolderPath) paths.push(openFolderPath)
+    return [...new Set(paths)].sort().join('\n')
+  }, [tabs, openFolderPath])
 
-  // Check if file was modified externally
-  const checkActiveFile = useCallback(async () => {
-    if (!activeTab?.filePath || isCheckingRef.current) return
-
-    const tabId = activeTab.id
-    const filePath = activeTab.filePath
+  // Sync watched paths with open tabs and workspace folder
+  useEffect(() => {
+    const wanted = new Set(watchKey ? watchKey.split('\n') : [])
+    const watched = watchedRef.current
+
+    for (const path of [...watched]) {
+      if (!wanted.has(path)) {
+        watched.delete(path)
+        invoke('unwatch_path', { path }).catch(error => {
+          console.warn(`Failed to unwatch ${path}:`, error)
+        })
+      }
+    }
 
-    // Don't check too frequently (minimum 5 seconds between checks for same file)
-    const lastChecked = lastCheckedRef.current.get(tabId) || 0
-    if (Date.now() - lastChecked < 5000) return
+    for (const path of wanted) {
+      if (!watched.has(path)) {
+        watched.add(path)
+        invoke('watch_path', { path }).catch(error => {
+          // File might have been deleted or moved - don't spam errors
+          console.warn(`Failed to watch ${path}:`, error)
+          watched.delete(path)
+        })
+      }
+    }
+  }, [watchKey])
 
-    isCheckingRef.current = true
+  // Release all watches on unmount
+  useEffect(() => {
+    const watched = watchedRef.current
+    return () => {
+      for (const path of watched) {
+        invoke('unwatch_path', { path }).catch(() => {})
+      }
+      watched.clear()
+    }
+  }, [])
 
-    try {
-      const currentModTime = await invoke<number>('get_file_modified_time', {
-        path: filePath
+  // Listen for watcher events
+  useEffect(() => {
+    const unlisteners: Array<() => void> = []
+
+    const notifyChanged = (title: string) => {
+      addNotification({
+        type: 'warning',
+        message: `File "${title}" changed externally`,
+        details: 'The file was modified outside the editor. Click to reload.',
+        duration: 10000
       })
+    }
+
+    const mergeExternalChange = async (tabId: string, path: string) => {
+      const tab = useTabStore.getState().tabs.find(t => t.id === tabId)
+      if (!tab) return
 
-      lastCheckedRef.current.set(tabId, Date.now())
+      let result: MergeResult
+      try {
+        result = await invoke<MergeResult>('merge_file_changes', {
+          path,
+          base: getMergeBase(tabId) ?? null,
+          buffer: tab.content
+        })
+      } catch (error) {
+        // No base to merge against: fall back to a plain warning
+        console.warn(`Failed to merge ${path}:`, error)
+        notifyChanged(tab.title)
+        return
+      }
 
-      // Store the modified time when we first load the file
-      if (!activeTab.lastModifiedTime) {
-        updateTab(tabId, { lastModifiedTime: currentModTime })
+      // Don't overwrite edits made while the merge ran
+      const current = useTabStore.getState().tabs.find(t => t.id === tabId)
+      if (!current) return
+      if (current.content !== tab.content) {
+        notifyChanged(tab.title)
         return
       }
 
-      // Check if file was modified externally
-      if (currentModTime > activeTab.lastModifiedTime) {
-        // Show notification instead of blocking confirm dialog
+      if (result.merged !== null) {
+        setMergeBase(tabId, result.theirs)
+        updateTab(tabId, { content: result.merged, isDirty: result.merged !== result.theirs })
         addNotification({
-          type: 'warning',
-          message: `File "${activeTab.title}" changed externally`,
-          details: 'The file was modified outside the editor. Click to reload.',
-          duration: 10000
+          type: 'info',
+          message: `Merged external changes into "${tab.title}"`,
+          details: 'Your unsaved edits were kept.'
         })
-
-        // Update the modified time to prevent repeated notifications
-        updateTab(tabId, { lastModifiedTime: currentModTime })
+        return
       }
-    } catch (error) {
-      // File might have been deleted or moved - don't spam errors
-      console.warn(`Error checking file ${filePath}:`, error)
-    } finally {
-      isCheckingRef.current = false
-    }
-  }, [activeTab, updateTab, addNotification])
 
-  // Check on tab switch
-  useEffect(() => {
-    if (activeTab?.filePath) {
-      checkActiveFile()
+      useMergeStore.getState().setPending({
+        tabId,
+        title: tab.title,
+        hunks: result.hunks,
+        theirs: result.theirs
+      })
     }
-  }, [activeTabId, checkActiveFile])
-
-  // Set up interval for active tab only - check every 5 seconds
-  useEffect(() => {
-    if (!activeTab?.filePath) return
 
-    const interval = setInterval(checkActiveFile, 5000)
-
-    return () => clearInterval(interval)
-  }, [activeTabId, checkActiveFile])
+    const setupListeners = async () => {
+      unlisteners.push(await listen<FileChangedPayload>('file-changed', (event) => {
+        const { path, is_dir, modified } = event.payload
+        if (is_dir) return
+
+        const affectedTabs = useTabStore.getState().tabs.filter(t => t.filePath === path)
+        for (const tab of affectedTabs) {
+          if (modified !== null) {
+            updateTab(tab.id, { lastModifiedTime: modified })
+          }
+
+          if (tab.isDirty) {
+            mergeExternalChange(tab.id, path)
+            continue
+          }
+
+          // Show notification instead of blocking confirm dialog
+          notifyChanged(tab.title)
+        }
+      }))
+
+      unlisteners.push(await listen<FileDeletedPayload>('file-deleted', (event) => {
+        const affectedTabs = useTabStore.getState().tabs.filter(t => t.filePath === event.payload.path)
+        for (const tab of affectedTabs) {
+          addNotification({
+            type: 'warning',
+            message: `File "${tab.title}" was deleted externally`,
+            details: 'The tab content is kept. Save to restore the file.',
+            duration: 10000
+          })
+        }
+      }))
+
+      unlisteners.push(await listen<FileRenamedPayload>('file-renamed', (event) => {
+        const { from, to } = event.payload
+        const title = to.replace(/\\/g, '/').split('/').pop() || to
+
+        // The backend already follows the renamed file
+        if (watchedRef.current.delete(from)) {
+          watchedRef.current.add(to)
+        }
+
+        const affectedTabs = useTabStore.getState().tabs.filter(t => t.filePath === from)
+        for (const tab of affectedTabs) {
+          updateTab(tab.id, { filePath: to, title })
+        }
+      }))
+    }
 
-  // Clean up refs when tabs are removed
-  useEffect(() => {
-    const currentTabIds = new Set(tabs.map(t => t.id))
+    setupListeners()
 
-    for (const tabId of lastCheckedRef.current.keys()) {
-      if (!currentTabIds.has(tabId)) {
-        lastCheckedRef.current.delete(tabId)
-      }
+    return () => {
+      unlisteners.forEach(unlisten => unlisten())
     }
-  }, [tabs])
+  }, [updateTab, addNotification])
 }
@@ -0,0 +1,37 @@
+import { useEffect } from 'react'
+import { useTabStore } from '../store/tabStore'
+import { useGitStore } from '../store/gitStore'
+
+const REFRESH_DELAY_MS = 500
+
+/**
+ * Hook to keep file tree git status current
+ * Re-reads status when a file-backed tab is saved (goes from dirty to clean)
+ * and when the window regains focus, since commits and checkouts usually
+ * happen in another program.
+ */
+export function useGitStatus() {
+  useEffect(() => {
+    let timer: number | undefined
+    const scheduleRefresh = () => {
+      window.clearTimeout(timer)
+      timer = window.setTimeout(() => useGitStore.getState().refresh(), REFRESH_DELAY_MS)
+    }
+
+    const unsubscribe = useTabStore.subscribe((state, prev) => {
+      const saved = state.tabs.some(tab => {
+        if (!tab.filePath || tab.isDirty) return false
+        const before = prev.tabs.find(t => t.id === tab.id)
+        return before?.isDirty === true || (before !== undefined && before.filePath !== tab.filePath)
+      })
+      if (saved) scheduleRefresh()
+    })
+
+    window.addEventListener('focus', scheduleRefresh)
+    return () => {
+      unsubscribe()
+      window.removeEventListener('focus', scheduleRefresh)
+      window.clearTimeout(timer)
+    }
+  }, [])
+}
@@ -0,0 +1,200 @@
+import { useEffect } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { useTabStore, Tab } from '../store/tabStore'
+import { useNotificationStore } from '../store/notificationStore'
+import { detectLanguage } from '../utils/languageExtensions'
+
+interface RecoverableBuffer {
+  tab_id: string
+  path: string | null
+  title: string
+  content: string
+  journal_modified: number
+  file_modified: number | null
+  // The file changed on disk after these edits were journaled
+  conflict: boolean
+}
+
+interface JournalChange {
+  from: number
+  to: number
+  insert: string
+}
+
+// Edits are batched per tab before being appended to the journal
+const JOURNAL_DEBOUNCE_MS = 1000
+
+/**
+ * Smallest single splice turning `before` into `after`
+ * Offsets are UTF-16 code units, matching the Rust side
+ */
+const computeChange = (before: string, after: string): JournalChange => {
+  const maxPrefix = Math.min(before.length, after.length)
+  let prefix = 0
+  while (prefix < maxPrefix && before.charCodeAt(prefix) === after.charCodeAt(prefix)) prefix++
+
+  const maxSuffix = maxPrefix - prefix
+  let suffix = 0
+  while (
+    suffix < maxSuffix &&
+    before.charCodeAt(before.length - 1 - suffix) === after.charCodeAt(after.length - 1 - suffix)
+  ) suffix++
+
+  return {
+    from: prefix,
+    to: before.length - suffix,
+    insert: after.slice(prefix, after.length - suffix)
+  }
+}
+
+/**
+ * Hook to journal unsaved buffers on the Rust side
+ * Dirty tabs are mirrored into a per-tab journal in the app data dir, so
+ * drafts survive a cleared WebView profile or a crash mid-save. On startup
+ * any journal that differs from its file is offered for recovery, with files
+ * changed since flagged so recovering doesn't silently replace them.
+ */
+export function useRecoveryJournal() {
+  const isInitialized = useTabStore(state => state.isInitialized)
+
+  useEffect(() => {
+    if (!isInitialized) return
+
+    let unsubscribe: (() => void) | null = null
+    let cancelled = false
+    // Content last written to each tab's journal
+    const journaled = new Map<string, string>()
+    const timers = new Map<string, number>()
+
+    const checkpoint = async (tab: Tab) => {
+      journaled.set(tab.id, tab.content)
+      await invoke('journal_checkpoint', {
+        tabId: tab.id,
+        path: tab.filePath,
+        title: tab.title,
+        content: tab.content
+      })
+    }
+
+    const flush = async (tabId: string) => {
+      timers.delete(tabId)
+      const tab = useTabStore.getState().tabs.find(t => t.id === tabId)
+      if (!tab || !tab.isDirty) return
+
+      const previous = journaled.get(tabId)
+      if (previous === tab.content) return
+
+      try {
+        if (previous === undefined) {
+          await checkpoint(tab)
+          return
+        }
+        journaled.set(tabId, tab.content)
+        await invoke('journal_append', { tabId, changes: [computeChange(previous, tab.content)] })
+      } catch {
+        // Missing or unreadable journal: start over from the full buffer
+        await checkpoint(tab).catch(error => console.error('Failed to journal tab:', error))
+      }
+    }
+
+    const discard = (tabId: string) => {
+      const timer = timers.get(tabId)
+      if (timer) window.clearTimeout(timer)
+      timers.delete(tabId)
+      journaled.delete(tabId)
+      invoke('journal_discard', { tabId }).catch(error => {
+        console.error('Failed to discard journal:', error)
+      })
+    }
+
+    const recover = async (buffers: RecoverableBuffer[]) => {
+      const { tabs, addTab, updateTab } = useTabStore.getState()
+
+      for (const buffer of buffers) {
+        const existing = tabs.find(t => t.id === buffer.tab_id) ||
+          (buffer.path ? tabs.find(t => t.filePath === buffer.path) : undefined)
+
+        if (existing) {
+          if (existing.id !== buffer.tab_id) discard(buffer.tab_id)
+          updateTab(existing.id, { content: buffer.content, isDirty: true })
+          continue
+        }
+
+        const language = buffer.path ? await detectLanguage(buffer.path, buffer.content) : 'markdown'
+        addTab({
+          id: buffer.tab_id,
+          title: buffer.title,
+          content: buffer.content,
+          filePath: buffer.path,
+          language,
+          isDirty: true
+        })
+      }
+    }
+
+    const start = async () => {
+      let buffers: RecoverableBuffer[] = []
+      try {
+        buffers = await invoke<RecoverableBuffer[]>('list_recoverable_buffers')
+      } catch (error) {
+        console.error('Failed to read recovery journal:', error)
+      }
+      if (cancelled) return
+
+      // Buffers IndexedDB already restored verbatim need no prompt
+      const tabs = useTabStore.getState().tabs
+      const pending = buffers.filter(buffer =>
+        !tabs.some(t => t.id === buffer.tab_id && t.isDirty && t.content === buffer.content)
+      )
+
+      if (pending.length > 0) {
+        const names = pending
+          .map(b => `${b.path || b.title}${b.conflict ? ' (file changed on disk since)' : ''}`)
+          .join('\n')
+        if (confirm(`Unsaved changes were found for:\n${names}\n\nRecover them?`)) {
+          await recover(pending)
+          useNotificationStore.getState().addNotification({
+            type: 'info',
+            message: 'Unsaved changes recovered',
+            details: `${pending.length} buffer(s) restored from the recovery journal`
+          })
+        } else {
+          pending.forEach(buffer => discard(buffer.tab_id))
+        }
+      }
+
+      // Journals are rebuilt from scratch for everything still dirty
+      for (const tab of useTabStore.getState().tabs) {
+        if (tab.isDirty) {
+          await checkpoint(tab).catch(error => console.error('Failed to journal tab:', error))
+        }
+      }
+      if (cancelled) return
+
+      unsubscribe = useTabStore.subscribe((state, prev) => {
+        const current = new Map(state.tabs.map(t => [t.id, t]))
+
+        for (const old of prev.tabs) {
+          if (!current.has(old.id) && journaled.has(old.id)) discard(old.id)
+        }
+
+        for (const tab of state.tabs) {
+          if (!tab.isDirty) {
+            if (journaled.has(tab.id)) discard(tab.id)
+            continue
+          }
+          if (journaled.get(tab.id) === tab.content || timers.has(tab.id)) continue
+          timers.set(tab.id, window.setTimeout(() => flush(tab.id), JOURNAL_DEBOUNCE_MS))
+        }
+      })
+    }
+
+    start()
+
+    return () => {
+      cancelled = true
+      unsubscribe?.()
+      timers.forEach(timer => window.clearTimeout(timer))
+    }
+  }, [isInitialized])
+}
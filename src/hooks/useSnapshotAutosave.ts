@@ -0,0 +1,39 @@
+import { useEffect } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { useTabStore, getHistoryOptions } from '../store/tabStore'
+
+/**
+ * Hook to snapshot unsaved changes into local history
+ * Every historyConfig.autosaveMinutes, each dirty tab backed by a file is
+ * recorded with record_snapshot. Unchanged content is deduplicated in Rust.
+ */
+export function useSnapshotAutosave() {
+  const enabled = useTabStore(state => state.viewSettings.historyConfig?.enabled ?? false)
+  const minutes = useTabStore(state => state.viewSettings.historyConfig?.autosaveMinutes ?? 0)
+
+  useEffect(() => {
+    if (!enabled || minutes <= 0) return
+
+    const interval = window.setInterval(() => {
+      const options = getHistoryOptions()
+      if (!options) return
+
+      for (const tab of useTabStore.getState().tabs) {
+        if (!tab.filePath || !tab.isDirty) continue
+
+        invoke('record_snapshot', {
+          path: tab.filePath,
+          content: tab.content,
+          encoding: tab.encoding,
+          hasBom: tab.hasBom,
+          lineEnding: tab.lineEnding,
+          options
+        }).catch(error => {
+          console.error(`Failed to snapshot ${tab.filePath}:`, error)
+        })
+      }
+    }, minutes * 60 * 1000)
+
+    return () => window.clearInterval(interval)
+  }, [enabled, minutes])
+}
@@ -1,61 +1,269 @@
 import { useEffect } from 'react'
 import { listen } from '@tauri-apps/api/event'
 import { invoke } from '@tauri-apps/api/core'
+import type { EditorView } from '@codemirror/view'
 import { useTabStore } from '../store/tabStore'
+import { useDiffStore } from '../store/diffStore'
+import { useNotificationStore } from '../store/notificationStore'
+import { useTemplateStore } from '../store/templateStore'
+import { FileContent, OpenFailure, OpenRequest, PendingOpen } from '../types/file'
+import { detectLanguage } from '../utils/languageExtensions'
+import { getErrorMessage } from '../utils/errorHandler'
+import {
+  extractTemplateVariables,
+  fillTemplateVariables,
+  processTemplateVariables
+} from '../utils/templateVariables'
+
+// How long to wait for a new tab's editor before giving up on the cursor position
+const EDITOR_WAIT_MS = 5000
+
+const fileName = (path: string) => path.split(/[\\/]/).pop() || path
+
+const reportFailures = (failures: OpenFailure[]) => {
+  if (failures.length === 0) return
+  useNotificationStore.getState().addNotification({
+    type: 'error',
+    message: failures.length === 1
+      ? `Couldn't open ${fileName(failures[0].path)}`
+      : `Couldn't open ${failures.length} files`,
+    details: failures.map(failure => `${failure.path}: ${failure.reason}`).join('\n')
+  })
+}
+
+const placeCursor = (view: EditorView, line: number, column: number | null) => {
+  const doc = view.state.doc
+  const target = doc.line(Math.min(Math.max(line, 1), doc.lines))
+  const offset = Math.min(Math.max((column ?? 1) - 1, 0), target.length)
+  view.dispatch({
+    selection: { anchor: target.from + offset },
+    scrollIntoView: true
+  })
+  view.focus()
+}
+
+/**
+ * Moves the cursor once the tab's editor exists; a tab added a moment ago
+ * hasn't mounted yet.
+ */
+const revealPosition = (tabId: string, line: number, column: number | null) => {
+  const findView = () =>
+    useTabStore.getState().tabs.find(t => t.id === tabId)?.editorView as EditorView | undefined
+
+  const view = findView()
+  if (view) {
+    placeCursor(view, line, column)
+    return
+  }
+
+  const timeout = window.setTimeout(() => unsubscribe(), EDITOR_WAIT_MS)
+  const unsubscribe = useTabStore.subscribe(() => {
+    const mounted = findView()
+    if (!mounted) return
+    unsubscribe()
+    window.clearTimeout(timeout)
+    placeCursor(mounted, line, column)
+  })
+}
+
+/**
+ * Opens what a launch asked for: files at their positions, read-only or in
+ * new tabs, and a diff view for --diff.
+ */
+const openRequest = async (request: OpenRequest) => {
+  const { addTab, setActiveTab, updateTab, addRecentFile } = useTabStore.getState()
+  const failures: OpenFailure[] = []
+
+  for (const file of request.files) {
+    const filePath = file.path
+    try {
+      let tabId: string
+      const existingTab = request.new_tab
+        ? undefined
+        : useTabStore.getState().tabs.find(t => t.filePath === filePath)
+
+      if (existingTab) {
+        setActiveTab(existingTab.id)
+        if (request.readonly && !existingTab.readOnly) {
+          updateTab(existingTab.id, { readOnly: true })
+        }
+        tabId = existingTab.id
+      } else {
+        // Read and open file
+        const content = await invoke<FileContent>('read_file', { path: filePath })
+        const title = await invoke<string>('get_file_name', { path: filePath })
+        const language = await detectLanguage(filePath, content.content)
+
+        addTab({
+          title,
+          content: content.content,
+          encoding: content.encoding,
+          hasBom: content.has_bom,
+          lineEnding: content.line_ending,
+          filePath,
+          language,
+          isDirty: false,
+          readOnly: request.readonly || undefined
+        })
+        tabId = useTabStore.getState().activeTabId as string
+        addRecentFile(filePath)
+      }
+
+      if (file.line !== null) {
+        revealPosition(tabId, file.line, file.column)
+      }
+    } catch (error) {
+      console.error(`Failed to open startup file: ${filePath}`, error)
+      failures.push({ path: filePath, reason: getErrorMessage(error) })
+    }
+  }
+  reportFailures(failures)
+
+  if (request.diff) {
+    const { old: oldPath, new: newPath } = request.diff
+    useDiffStore.getState().openDiff({
+      title: `${fileName(oldPath)} vs. ${fileName(newPath)}`,
+      oldInput: { path: oldPath },
+      newInput: { path: newPath },
+      oldLabel: oldPath,
+      newLabel: newPath
+    })
+  }
+}
+
+const untitledTitle = () => {
+  const count = useTabStore.getState().tabs.filter(t => t.title.startsWith('Untitled')).length
+  return `Untitled-${count + 1}`
+}
+
+// contextpad://new
+const openNewTab = (title: string | null, content: string) => {
+  useTabStore.getState().addTab({
+    title: title ?? untitledTitle(),
+    content,
+    language: 'markdown',
+    // Nothing on disk holds this text yet
+    isDirty: content.length > 0
+  })
+}
+
+// contextpad://template/<id>, with the link's variables filled in
+const openTemplate = (id: string, variables: Record<string, string>) => {
+  const { addNotification } = useNotificationStore.getState()
+  const template = useTemplateStore.getState().templates.find(t => t.id === id)
+  if (!template) {
+    addNotification({
+      type: 'error',
+      message: 'Template not found',
+      details: `No template has the id "${id}"`
+    })
+    return
+  }
+
+  const filled = fillTemplateVariables(template.content, variables)
+  const { content, cursorOffset } = processTemplateVariables(filled)
+  const { addTab } = useTabStore.getState()
+  addTab({
+    title: template.name,
+    content,
+    language: 'markdown',
+    isDirty: true
+  })
+
+  if (cursorOffset !== null) {
+    const before = content.slice(0, cursorOffset).split('\n')
+    const tabId = useTabStore.getState().activeTabId as string
+    revealPosition(tabId, before.length, before[before.length - 1].length + 1)
+  }
+
+  const known = extractTemplateVariables(template.content)
+  const unknown = Object.keys(variables).filter(name => !known.includes(name))
+  if (unknown.length > 0) {
+    addNotification({
+      type: 'warning',
+      message: `${template.name} doesn't use ${unknown.length === 1 ? 'a variable' : 'some variables'} from the link`,
+      details: unknown.join(', ')
+    })
+  }
+}
+
+const handlePending = async (item: PendingOpen) => {
+  switch (item.type) {
+    case 'open_files':
+      await openRequest(item.request)
+      break
+    case 'open_files_failed':
+      reportFailures(item.failures)
+      break
+    case 'new_tab':
+      openNewTab(item.title, item.content)
+      break
+    case 'template':
+      openTemplate(item.id, item.variables)
+      break
+    case 'invalid_link':
+      useNotificationStore.getState().addNotification({
+        type: 'error',
+        message: "Couldn't open link",
+        details: `${item.url}\n${item.reason}`
+      })
+      break
+    case 'invalid_arguments':
+      useNotificationStore.getState().addNotification({
+        type: 'error',
+        message: "Couldn't read the launch arguments",
+        details: item.reason
+      })
+      break
+  }
+}
 
 /**
- * Hook to handle files passed via CLI arguments or "Open With" context menu
+ * Hook to handle files passed via CLI arguments, "Open With" or deep links
+ * The backend queues every request; once the listener is mounted the queue
+ * is drained, and again whenever the backend signals that more arrived, so
+ * nothing sent during startup is missed.
  */
 export function useStartupFiles() {
-  const addTab = useTabStore(state => state.addTab)
-  const setActiveTab = useTabStore(state => state.setActiveTab)
-  const addRecentFile = useTabStore(state => state.addRecentFile)
+  // Restoring saved tabs would replace anything opened before it finishes
+  const isInitialized = useTabStore(state => state.isInitialized)
 
   useEffect(() => {
+    if (!isInitialized) return
+
     let unlisten: (() => void) | null = null
+    let disposed = false
+    // Drains run one after another so requests are handled in order
+    let draining = Promise.resolve()
 
-    const setupListener = async () => {
-      // Listen for files opened via CLI or deep link
-      unlisten = await listen<string[]>('open-files', async (event) => {
-        const filePaths = event.payload
-        const currentTabs = useTabStore.getState().tabs
-
-        for (const filePath of filePaths) {
-          try {
-            // Check if already open
-            const existingTab = currentTabs.find(t => t.filePath === filePath)
-            if (existingTab) {
-              setActiveTab(existingTab.id)
-              continue
-            }
-
-            // Read and open file
-            const content = await invoke<string>('read_file', { path: filePath })
-            const fileName = await invoke<string>('get_file_name', { path: filePath })
-            const language = await invoke<string>('detect_language_from_path', { path: filePath })
-
-            addTab({
-              title: fileName,
-              content,
-              filePath,
-              language,
-              isDirty: false
-            })
-
-            addRecentFile(filePath)
-          } catch (error) {
-            console.error(`Failed to open startup file: ${filePath}`, error)
+    const drain = () => {
+      draining = draining
+        .then(async () => {
+          const pending = await invoke<PendingOpen[]>('take_pending_open_requests')
+          for (const item of pending) {
+            await handlePending(item)
           }
-        }
-      })
+        })
+        .catch(error => console.error('Failed to handle open requests:', error))
+    }
+
+    const setupListener = async () => {
+      const stop = await listen('open-requests-pending', drain)
+      if (disposed) {
+        stop()
+        return
+      }
+      unlisten = stop
+      drain()
     }
 
     setupListener()
 
     return () => {
+      disposed = true
       if (unlisten) {
         unlisten()
       }
     }
-  }, [addTab, setActiveTab, addRecentFile])
+  }, [isInitialized])
 }
@@ -1,14 +1,19 @@
 import React from 'react'
 import { createRoot } from 'react-dom/client'
-import App from './App'
+import { sqliteStorage } from './services/storage/SqliteStorage'
 import './styles/global.css'
 
 const rootElement = document.getElementById('root')
 if (!rootElement) throw new Error('Failed to find the root element')
 
-const root = createRoot(rootElement)
-root.render(
-  <React.StrictMode>
-    <App />
-  </React.StrictMode>
-)
+// Stores read persisted state when their modules load, so the storage cache
+// must be ready before App (and everything it imports) is evaluated
+sqliteStorage.init().then(async () => {
+  const { default: App } = await import('./App')
+  const root = createRoot(rootElement)
+  root.render(
+    <React.StrictMode>
+      <App />
+    </React.StrictMode>
+  )
+})
@@ -152,6 +152,30 @@ class IndexedDBStorage {
     })
   }
 
+  /**
+   * Get every stored tab content (used when migrating to SQLite)
+   */
+  async getAllTabContents(): Promise<TabContent[]> {
+    await this.init()
+    if (!this.isAvailable()) return []
+
+    return new Promise((resolve) => {
+      try {
+        const transaction = this.db!.transaction([CONTENT_STORE], 'readonly')
+        const request = transaction.objectStore(CONTENT_STORE).getAll()
+
+        request.onsuccess = () => resolve(request.result as TabContent[])
+        request.onerror = () => {
+          console.error('Failed to list tab contents:', request.error)
+          resolve([])
+        }
+      } catch (error) {
+        console.error('Error listing tab contents:', error)
+        resolve([])
+      }
+    })
+  }
+
   /**
    * Delete tab content from IndexedDB
    */
@@ -0,0 +1,238 @@
+/**
+ * SQLite Storage Service
+ * Persists stores in the profile database owned by the Rust side, so data
+ * survives a cleared WebView profile. Key/value reads are served from an
+ * in-memory cache loaded once at startup, which keeps the synchronous
+ * localStorage-style API the stores were written against.
+ */
+
+import { invoke } from '@tauri-apps/api/core'
+import type { StateStorage } from 'zustand/middleware'
+import { indexedDBStorage, TabContent } from './IndexedDBStorage'
+
+const CONTENT_COLLECTION = 'tab-contents'
+// Set once browser storage has been copied into the database
+const IMPORT_MARKER_KEY = 'contextpad-storage-imported'
+const BROWSER_KEY_PREFIX = 'contextpad'
+// Stores persist on every change (the tab list on each keystroke); writes to
+// a key within this window are coalesced into one, with the last value winning
+const WRITE_DELAY_MS = 500
+
+interface StorageInfo {
+  path: string
+  schema_version: number
+  available: boolean
+}
+
+interface StoredDocument<T> {
+  id: string
+  data: T
+  updated_at: number
+}
+
+class SqliteStorage implements StateStorage {
+  private cache = new Map<string, string>()
+  private initPromise: Promise<void> | null = null
+  private isSupported: boolean = false
+  // Set while a profile import replaces the data underneath the cache
+  private writesSuspended = false
+  // Latest value per key not yet written; null removes the key
+  private pendingWrites = new Map<string, string | null>()
+  private writeTimer: number | null = null
+  // Flushes run one after another so an older value never lands last
+  private writeChain: Promise<void> = Promise.resolve()
+
+  async init(): Promise<void> {
+    if (this.initPromise) return this.initPromise
+
+    this.initPromise = (async () => {
+      try {
+        const info = await invoke<StorageInfo>('storage_info')
+        if (!info.available) {
+          console.warn('SQLite storage unavailable, falling back to browser storage')
+          return
+        }
+
+        this.isSupported = true
+        window.addEventListener('beforeunload', () => this.flush())
+        await this.loadCache()
+        if (!this.cache.has(IMPORT_MARKER_KEY)) {
+          await this.importBrowserStorage()
+        }
+      } catch (error) {
+        console.error('SQLite storage initialization error:', error)
+        this.isSupported = false
+      }
+    })()
+
+    return this.initPromise
+  }
+
+  /**
+   * Check if the database is available; otherwise calls go to browser storage
+   */
+  isAvailable(): boolean {
+    return this.isSupported
+  }
+
+  private async loadCache() {
+    const entries = await invoke<Record<string, string>>('storage_entries', { prefix: null })
+    this.cache = new Map(Object.entries(entries))
+  }
+
+  /**
+   * One-time copy of localStorage and IndexedDB data into the database.
+   * Browser storage is left in place so older builds can still read it.
+   */
+  private async importBrowserStorage() {
+    const entries: Record<string, string> = {}
+    for (let i = 0; i < localStorage.length; i++) {
+      const key = localStorage.key(i)
+      if (key && key.startsWith(BROWSER_KEY_PREFIX)) {
+        entries[key] = localStorage.getItem(key) ?? ''
+      }
+    }
+
+    const contents = await indexedDBStorage.getAllTabContents()
+    const documents = contents.map(item => ({
+      collection: CONTENT_COLLECTION,
+      id: item.tabId,
+      data: item
+    }))
+
+    const summary = await invoke<{ entries: number; documents: number }>('storage_import', {
+      data: { entries, documents },
+      overwrite: false
+    })
+    if (summary.entries > 0 || summary.documents > 0) {
+      console.info(`Imported ${summary.entries} setting(s) and ${summary.documents} tab(s) into SQLite storage`)
+    }
+
+    await invoke('storage_set', { key: IMPORT_MARKER_KEY, value: String(Date.now()) })
+    await this.loadCache()
+  }
+
+  /**
+   * Stop persisting store changes until the page reloads, so values still in
+   * memory can't overwrite what a profile import is writing
+   */
+  suspendWrites(): void {
+    this.writesSuspended = true
+    this.pendingWrites.clear()
+  }
+
+  resumeWrites(): void {
+    this.writesSuspended = false
+  }
+
+  private scheduleWrite(key: string, value: string | null) {
+    this.pendingWrites.set(key, value)
+    if (this.writeTimer === null) {
+      this.writeTimer = window.setTimeout(() => this.flush(), WRITE_DELAY_MS)
+    }
+  }
+
+  /**
+   * Write out pending key/value changes now
+   */
+  flush(): Promise<void> {
+    if (this.writeTimer !== null) {
+      window.clearTimeout(this.writeTimer)
+      this.writeTimer = null
+    }
+    const writes = [...this.pendingWrites]
+    this.pendingWrites.clear()
+    if (writes.length === 0) return this.writeChain
+
+    this.writeChain = this.writeChain.then(() => Promise.all(writes.map(([key, value]) => {
+      const write = value === null
+        ? invoke('storage_remove', { key })
+        : invoke('storage_set', { key, value })
+      return write.catch(error => {
+        console.error(`Failed to persist ${key}:`, error)
+      })
+    })).then(() => undefined))
+    return this.writeChain
+  }
+
+  getItem(key: string): string | null {
+    if (!this.isSupported) return localStorage.getItem(key)
+    return this.cache.get(key) ?? null
+  }
+
+  setItem(key: string, value: string): void {
+    if (this.writesSuspended) return
+    if (!this.isSupported) {
+      localStorage.setItem(key, value)
+      return
+    }
+    this.cache.set(key, value)
+    this.scheduleWrite(key, value)
+  }
+
+  removeItem(key: string): void {
+    if (this.writesSuspended) return
+    if (!this.isSupported) {
+      localStorage.removeItem(key)
+      return
+    }
+    this.cache.delete(key)
+    this.scheduleWrite(key, null)
+  }
+
+  /**
+   * Save tab content
+   */
+  async saveTabContent(tabId: string, content: string): Promise<boolean> {
+    await this.init()
+    if (this.writesSuspended) return false
+    if (!this.isSupported) return indexedDBStorage.saveTabContent(tabId, content)
+
+    const data: TabContent = { tabId, content, lastModified: Date.now() }
+    try {
+      await invoke('document_put', { collection: CONTENT_COLLECTION, id: tabId, data })
+      return true
+    } catch (error) {
+      console.error('Failed to save tab content:', error)
+      return false
+    }
+  }
+
+  /**
+   * Get tab content
+   */
+  async getTabContent(tabId: string): Promise<string | null> {
+    await this.init()
+    if (!this.isSupported) return indexedDBStorage.getTabContent(tabId)
+
+    try {
+      const document = await invoke<StoredDocument<TabContent> | null>('document_get', {
+        collection: CONTENT_COLLECTION,
+        id: tabId
+      })
+      return document?.data?.content ?? null
+    } catch (error) {
+      console.error('Failed to get tab content:', error)
+      return null
+    }
+  }
+
+  /**
+   * Delete a tab's stored content
+   */
+  async deleteTab(tabId: string): Promise<boolean> {
+    await this.init()
+    if (this.writesSuspended) return false
+    if (!this.isSupported) return indexedDBStorage.deleteTab(tabId)
+
+    try {
+      return await invoke<boolean>('document_delete', { collection: CONTENT_COLLECTION, id: tabId })
+    } catch (error) {
+      console.error('Failed to delete tab content:', error)
+      return false
+    }
+  }
+}
+
+// Export singleton instance
+export const sqliteStorage = new SqliteStorage()
@@ -1,42 +1,31 @@
 /**
  * Local Estimator - Uses tiktoken for offline token counting
  *
- * Supports OpenAI models with cached encoder instances
- * Falls back to cl100k_base if specific encoding unavailable
+ * Encoding runs in the Rust backend on a worker thread, so counting a large
+ * document doesn't block the UI
  */
 
-import { getEncoding, Tiktoken } from 'js-tiktoken'
+import { invoke } from '@tauri-apps/api/core'
 import type { ModelDefinition, TiktokenEncoding } from '../models'
 import type { IEstimator, EstimatorResult } from './types'
 import { EstimatorError } from './types'
 
-export class LocalEstimator implements IEstimator {
-  // Cache encoder instances to avoid re-initialization
-  private encoderCache = new Map<TiktokenEncoding, Tiktoken>()
-
-  /**
-   * Get or create an encoder for the specified encoding
-   */
-  private getEncoder(encoding: TiktokenEncoding): Tiktoken {
-    let encoder = this.encoderCache.get(encoding)
-
-    if (!encoder) {
-      try {
-        encoder = getEncoding(encoding)
-        this.encoderCache.set(encoding, encoder)
-      } catch (error) {
-        // Fallback to cl100k_base if requested encoding unavailable
-        console.warn(`Encoding ${encoding} not available, falling back to cl100k_base`)
+/**
+ * Result of the count_tokens commands
+ */
+export interface TokenCount {
+  tokens: number
+  // Only when requested with includeIds
+  ids: number[] | null
+}
 
-        encoder = this.encoderCache.get('cl100k_base')
-        if (!encoder) {
-          encoder = getEncoding('cl100k_base')
-          this.encoderCache.set('cl100k_base', encoder)
-        }
-      }
+export class LocalEstimator implements IEstimator {
+  private async count(content: string, encoding: TiktokenEncoding, includeIds = false): Promise<TokenCount> {
+    try {
+      return await invoke<TokenCount>('count_tokens', { text: content, encoding, includeIds })
+    } catch (error) {
+      throw new EstimatorError('ENCODING_ERROR', `Failed to encode content: ${String(error)}`)
     }
-
-    return encoder
   }
 
   /**
@@ -57,21 +46,12 @@ export class LocalEstimator implements IEstimator {
       )
     }
 
-    try {
-      const encoder = this.getEncoder(model.encoding)
-      const tokens = encoder.encode(content)
-
-      return {
-        tokens: tokens.length,
-        method: 'local',
-        model: model.id,
-        cached: false
-      }
-    } catch (error) {
-      throw new EstimatorError(
-        'ENCODING_ERROR',
-        `Failed to encode content: ${error instanceof Error ? error.message : 'Unknown error'}`
-      )
+    const { tokens } = await this.count(content, model.encoding)
+    return {
+      tokens,
+      method: 'local',
+      model: model.id,
+      cached: false
     }
   }
 
@@ -82,23 +62,31 @@ export class LocalEstimator implements IEstimator {
    * @returns Token count for the content
    */
   async estimatePartial(content: string, encoding: TiktokenEncoding = 'o200k_base'): Promise<number> {
+    const { tokens } = await this.count(content, encoding)
+    return tokens
+  }
+
+  /**
+   * Count each section separately in a single backend call
+   * @param sections - Texts to count, e.g. a document split at its headings
+   * @param encoding - The tiktoken encoding to use
+   * @returns Token count per section, in order
+   */
+  async estimateSections(sections: string[], encoding: TiktokenEncoding = 'o200k_base'): Promise<number[]> {
     try {
-      const encoder = this.getEncoder(encoding)
-      const tokens = encoder.encode(content)
-      return tokens.length
+      const counts = await invoke<TokenCount[]>('count_tokens_batch', { texts: sections, encoding })
+      return counts.map(count => count.tokens)
     } catch (error) {
-      throw new EstimatorError(
-        'ENCODING_ERROR',
-        `Failed to encode content: ${error instanceof Error ? error.message : 'Unknown error'}`
-      )
+      throw new EstimatorError('ENCODING_ERROR', `Failed to encode content: ${String(error)}`)
     }
   }
 
   /**
-   * Free encoder resources (call when done with estimator)
+   * Token IDs for the content, for debugging how text is split
    */
-  dispose(): void {
-    this.encoderCache.clear()
+  async encode(content: string, encoding: TiktokenEncoding = 'o200k_base'): Promise<number[]> {
+    const { ids } = await this.count(content, encoding, true)
+    return ids ?? []
   }
 }
 
@@ -1,4 +1,5 @@
 import { create } from 'zustand'
+import { sqliteStorage } from '../services/storage/SqliteStorage'
 
 export type ActionType = 'button' | 'command'
 
@@ -76,7 +77,7 @@ interface PersistedUIState {
 // Load persisted actions
 const loadPersistedActions = (): Action[] => {
   try {
-    const saved = localStorage.getItem(STORAGE_KEY_ACTIONS)
+    const saved = sqliteStorage.getItem(STORAGE_KEY_ACTIONS)
     if (saved) {
       return JSON.parse(saved) as Action[]
     }
@@ -89,7 +90,7 @@ const loadPersistedActions = (): Action[] => {
 // Load persisted UI state
 const loadPersistedUI = (): PersistedUIState => {
   try {
-    const saved = localStorage.getItem(STORAGE_KEY_UI)
+    const saved = sqliteStorage.getItem(STORAGE_KEY_UI)
     if (saved) {
       return JSON.parse(saved)
     }
@@ -101,7 +102,7 @@ const loadPersistedUI = (): PersistedUIState => {
 
 const persistActions = (actions: Action[]) => {
   try {
-    localStorage.setItem(STORAGE_KEY_ACTIONS, JSON.stringify(actions))
+    sqliteStorage.setItem(STORAGE_KEY_ACTIONS, JSON.stringify(actions))
   } catch (error) {
     console.error('Failed to persist actions:', error)
   }
@@ -109,7 +110,7 @@ const persistActions = (actions: Action[]) => {
 
 const persistUI = (state: PersistedUIState) => {
   try {
-    localStorage.setItem(STORAGE_KEY_UI, JSON.stringify(state))
+    sqliteStorage.setItem(STORAGE_KEY_UI, JSON.stringify(state))
   } catch (error) {
     console.error('Failed to persist action UI state:', error)
   }
@@ -0,0 +1,23 @@
+import { create } from 'zustand'
+import { DiffInput } from '../types/file'
+
+export interface DiffRequest {
+  title: string
+  oldInput: DiffInput
+  newInput: DiffInput
+  oldLabel: string
+  newLabel: string
+}
+
+interface DiffState {
+  // Comparison shown in the app-level diff view, e.g. from `--diff`
+  request: DiffRequest | null
+  openDiff: (request: DiffRequest) => void
+  closeDiff: () => void
+}
+
+export const useDiffStore = create<DiffState>((set) => ({
+  request: null,
+  openDiff: (request) => set({ request }),
+  closeDiff: () => set({ request: null })
+}))
@@ -0,0 +1,82 @@
+import { create } from 'zustand'
+import { invoke } from '@tauri-apps/api/core'
+import { GitDirectoryStatus, GitFileStatus } from '../types/file'
+
+interface GitState {
+  // Working tree root of the open folder's repository, null outside one
+  repoRoot: string | null
+  branch: string | null
+  // Status per file tree entry, across every directory loaded so far
+  statuses: Record<string, GitFileStatus>
+  loadedDirs: string[]
+  // Any path inside the repository the commit dialog is open for
+  commitPath: string | null
+
+  loadDirectory: (path: string) => Promise<void>
+  // Re-reads every loaded directory, e.g. after a save or commit
+  refresh: () => Promise<void>
+  reset: () => void
+  setCommitPath: (path: string | null) => void
+}
+
+const fetchStatus = async (path: string): Promise<GitDirectoryStatus | null> => {
+  try {
+    return await invoke<GitDirectoryStatus | null>('git_status', { path })
+  } catch (error) {
+    console.error('Failed to read git status:', error)
+    return null
+  }
+}
+
+// Drops the previous entries of a directory before adding the fresh ones
+const replaceEntries = (
+  statuses: Record<string, GitFileStatus>,
+  dir: string,
+  entries: Record<string, GitFileStatus>
+) => {
+  const next: Record<string, GitFileStatus> = {}
+  for (const [path, status] of Object.entries(statuses)) {
+    const parent = path.slice(0, Math.max(path.lastIndexOf('/'), path.lastIndexOf('\\')))
+    if (parent !== dir) next[path] = status
+  }
+  return { ...next, ...entries }
+}
+
+export const useGitStore = create<GitState>((set, get) => ({
+  repoRoot: null,
+  branch: null,
+  statuses: {},
+  loadedDirs: [],
+  commitPath: null,
+
+  loadDirectory: async (path) => {
+    const status = await fetchStatus(path)
+    set(state => ({
+      repoRoot: status?.root ?? state.repoRoot,
+      branch: status?.branch ?? state.branch,
+      statuses: replaceEntries(state.statuses, path, status?.entries ?? {}),
+      loadedDirs: state.loadedDirs.includes(path) ? state.loadedDirs : [...state.loadedDirs, path]
+    }))
+  },
+
+  refresh: async () => {
+    const { loadedDirs } = get()
+    if (loadedDirs.length === 0) return
+
+    const results = await Promise.all(loadedDirs.map(fetchStatus))
+    let statuses: Record<string, GitFileStatus> = {}
+    for (const result of results) {
+      if (result) statuses = { ...statuses, ...result.entries }
+    }
+    const first = results.find(Boolean)
+    set({
+      repoRoot: first?.root ?? null,
+      branch: first?.branch ?? null,
+      statuses
+    })
+  },
+
+  reset: () => set({ repoRoot: null, branch: null, statuses: {}, loadedDirs: [] }),
+
+  setCommitPath: (path) => set({ commitPath: path })
+}))
@@ -0,0 +1,57 @@
+import { create } from 'zustand'
+import { MergeHunk } from '../types/file'
+import { useTabStore } from './tabStore'
+
+export interface PendingMerge {
+  tabId: string
+  title: string
+  hunks: MergeHunk[]
+  // Disk content the buffer is being merged with
+  theirs: string
+}
+
+// Content of each file-backed tab as of its last load or save, used as the
+// common ancestor when the file changes on disk. Kept out of tab metadata.
+const mergeBases = new Map<string, string>()
+
+export const getMergeBase = (tabId: string) => mergeBases.get(tabId)
+
+export const setMergeBase = (tabId: string, content: string) => {
+  mergeBases.set(tabId, content)
+}
+
+useTabStore.subscribe((state) => {
+  const open = new Set<string>()
+  for (const tab of state.tabs) {
+    open.add(tab.id)
+    if (tab.filePath && !tab.isDirty) mergeBases.set(tab.id, tab.content)
+  }
+  for (const tabId of mergeBases.keys()) {
+    if (!open.has(tabId)) mergeBases.delete(tabId)
+  }
+})
+
+interface MergeState {
+  pending: PendingMerge | null
+  setPending: (merge: PendingMerge | null) => void
+  // Applies the resolved text to the tab; disk content becomes the new base
+  resolve: (content: string) => void
+}
+
+export const useMergeStore = create<MergeState>((set, get) => ({
+  pending: null,
+
+  setPending: (merge) => set({ pending: merge }),
+
+  resolve: (content) => {
+    const { pending } = get()
+    if (!pending) return
+
+    setMergeBase(pending.tabId, pending.theirs)
+    useTabStore.getState().updateTab(pending.tabId, {
+      content,
+      isDirty: content !== pending.theirs
+    })
+    set({ pending: null })
+  }
+}))
@@ -1,5 +1,6 @@
 import { create } from 'zustand'
 import { persist, createJSONStorage } from 'zustand/middleware'
+import { sqliteStorage } from '../services/storage/SqliteStorage'
 import { TokenMethod } from '../types/tokenTypes'
 import { getDefaultModelId, getModelById, isValidModelId } from '../services/tokenEstimator/models'
 
@@ -129,7 +130,7 @@ export const useSettingsStore = create<SettingsState>()(
     {
       name: 'contextpad-settings',
       version: 3, // Increment version for new state
-      storage: createJSONStorage(() => localStorage),
+      storage: createJSONStorage(() => sqliteStorage),
 
       // Migration function to handle old settings
       migrate: (persistedState: any, version: number) => {
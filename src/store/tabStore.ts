@@ -1,6 +1,7 @@
 import { create } from 'zustand'
-import { indexedDBStorage } from '../services/storage/IndexedDBStorage'
+import { sqliteStorage } from '../services/storage/SqliteStorage'
 import welcomeContent from '../data/WELCOME.md?raw'
+import { LineEnding, WriteResult } from '../types/file'
 
 export interface Tab {
   id: string
@@ -11,8 +12,13 @@ export interface Tab {
   isDirty: boolean
   language: 'markdown' | string
   lastModifiedTime?: number
+  // On-disk format, preserved on save (defaults to UTF-8, no BOM, LF)
+  encoding?: string
+  hasBom?: boolean
+  lineEnding?: LineEnding
   editorView?: unknown // EditorView reference for outline parsing (not persisted)
   pinnedTabId?: string // If set, this tab is from a pinned workflow and should be locked
+  readOnly?: boolean // Opened with --readonly; the editor rejects changes
 }
 
 export interface CursorInfo {
@@ -77,6 +83,19 @@ export interface ViewSettings {
     enableHtmlLint: boolean
     enableJavaScriptLint: boolean
   }
+  // Save Safety
+  backupConfig: {
+    enabled: boolean
+    mode: 'rotate' | 'folder' // rotate = sibling .bak files, folder = <workspace>/.contextpad/backups
+    maxBackups: number
+  }
+  applyEditorConfig: boolean // Apply .editorconfig whitespace and EOL rules on save
+  historyConfig: {
+    enabled: boolean
+    maxRevisions: number
+    maxAgeDays: number // 0 = keep regardless of age
+    autosaveMinutes: number // Snapshot unsaved changes this often, 0 = only on save
+  }
 }
 
 interface TabState {
@@ -165,9 +184,55 @@ const defaultViewSettings: ViewSettings = {
     enableSqlLint: true,
     enableHtmlLint: true,
     enableJavaScriptLint: false
+  },
+  backupConfig: {
+    enabled: false,
+    mode: 'rotate',
+    maxBackups: 5
+  },
+  applyEditorConfig: true,
+  historyConfig: {
+    enabled: true,
+    maxRevisions: 50,
+    maxAgeDays: 30,
+    autosaveMinutes: 5
   }
 }
 
+/**
+ * Backup options for write_file, or null when backups are disabled
+ */
+export const getBackupOptions = () => {
+  const { viewSettings, openFolderPath } = useTabStore.getState()
+  const config = viewSettings.backupConfig
+  if (!config?.enabled) return null
+  return {
+    mode: config.mode,
+    max_backups: config.maxBackups,
+    workspace_root: openFolderPath
+  }
+}
+
+/**
+ * Snapshot retention for write_file and record_snapshot, or null when history is disabled
+ */
+export const getHistoryOptions = () => {
+  const config = useTabStore.getState().viewSettings.historyConfig
+  if (!config?.enabled) return null
+  return {
+    max_revisions: config.maxRevisions,
+    max_age_days: config.maxAgeDays
+  }
+}
+
+/**
+ * Tab updates that bring a buffer in line with what write_file put on disk
+ */
+export const getSavedFormat = (result: WriteResult): Partial<Tab> => ({
+  ...(result.content !== null && { content: result.content }),
+  ...(result.line_ending && { lineEnding: result.line_ending })
+})
+
 // Content save timers for debouncing
 const contentSaveTimers = new Map<string, number>()
 
@@ -180,7 +245,7 @@ const saveTabContentDebounced = (tabId: string, content: string) => {
 
   const timer = window.setTimeout(async () => {
     try {
-      await indexedDBStorage.saveTabContent(tabId, content)
+      await sqliteStorage.saveTabContent(tabId, content)
       contentSaveTimers.delete(tabId)
     } catch (error) {
       console.error(`Failed to save content for tab ${tabId}:`, error)
@@ -190,13 +255,13 @@ const saveTabContentDebounced = (tabId: string, content: string) => {
   contentSaveTimers.set(tabId, timer)
 }
 
-// Save metadata immediately (small data, goes to localStorage)
-const saveMetadataToLocalStorage = (state: TabState) => {
+// Save metadata on every change; the key/value store coalesces rapid writes
+const saveMetadata = (state: TabState) => {
   try {
     const metadataToSave = {
       tabs: state.tabs.map(({ editorView, content, ...tab }) => ({
         ...tab,
-        // Don't save content to localStorage anymore - it's in IndexedDB
+        // Content is stored separately, per tab
         content: ''
       })),
       activeTabId: state.activeTabId,
@@ -207,24 +272,24 @@ const saveMetadataToLocalStorage = (state: TabState) => {
       pinnedCategoryOrder: state.pinnedCategoryOrder,
       pinnedCollapsedCategories: state.pinnedCollapsedCategories
     }
-    localStorage.setItem('contextpad-tabs-v2', JSON.stringify(metadataToSave))
+    sqliteStorage.setItem('contextpad-tabs-v2', JSON.stringify(metadataToSave))
   } catch (error) {
     console.error('Failed to save metadata:', error)
   }
 }
 
-// Load metadata from localStorage
-const loadMetadataFromLocalStorage = () => {
+// Load metadata from the key/value store
+const loadMetadata = () => {
   try {
     // Try new format first
-    let savedState = localStorage.getItem('contextpad-tabs-v2')
+    let savedState = sqliteStorage.getItem('contextpad-tabs-v2')
 
     // Migrate from old format if needed
     if (!savedState) {
-      savedState = localStorage.getItem('contextpad-tabs')
+      savedState = sqliteStorage.getItem('contextpad-tabs')
       if (savedState) {
         // Migrate: copy to new key
-        localStorage.setItem('contextpad-tabs-v2', savedState)
+        sqliteStorage.setItem('contextpad-tabs-v2', savedState)
         // Keep old key for rollback capability
       }
     }
@@ -254,7 +319,9 @@ const loadMetadataFromLocalStorage = () => {
         viewSettings: safeViewSettings,
         recentFiles: parsed.recentFiles || [],
         openFolderPath: parsed.openFolderPath || null,
-        pinnedTabs
+        pinnedTabs,
+        pinnedCategoryOrder: parsed.pinnedCategoryOrder || [],
+        pinnedCollapsedCategories: parsed.pinnedCollapsedCategories || []
       }
     }
   } catch (error) {
@@ -275,7 +342,7 @@ const defaultPinnedTabs: PinnedTab[] = [
   }
 ]
 
-const persistedMetadata = loadMetadataFromLocalStorage()
+const persistedMetadata = loadMetadata()
 
 export const useTabStore = create<TabState>((set, get) => ({
   tabs: persistedMetadata?.tabs || [],
@@ -289,8 +356,8 @@ export const useTabStore = create<TabState>((set, get) => ({
   sidebarView: 'settings',
   isInitialized: false,
   pinnedTabs: persistedMetadata?.pinnedTabs || defaultPinnedTabs,
-  pinnedCategoryOrder: (persistedMetadata as any)?.pinnedCategoryOrder || [],
-  pinnedCollapsedCategories: (persistedMetadata as any)?.pinnedCollapsedCategories || [],
+  pinnedCategoryOrder: persistedMetadata?.pinnedCategoryOrder || [],
+  pinnedCollapsedCategories: persistedMetadata?.pinnedCollapsedCategories || [],
 
   // Initialize and load content from IndexedDB
   initializeFromStorage: async () => {
@@ -301,7 +368,7 @@ export const useTabStore = create<TabState>((set, get) => ({
       // Load content for each tab from IndexedDB
       const tabsWithContent = await Promise.all(
         state.tabs.map(async (tab) => {
-          const content = await indexedDBStorage.getTabContent(tab.id)
+          const content = await sqliteStorage.getTabContent(tab.id)
           return {
             ...tab,
             content: content || tab.content || ''
@@ -332,7 +399,7 @@ export const useTabStore = create<TabState>((set, get) => ({
           finalActiveId = welcomeTab.id
           
           // Persist immediately
-          saveMetadataToLocalStorage({ ...state, tabs: finalTabs, activeTabId: finalActiveId })
+          saveMetadata({ ...state, tabs: finalTabs, activeTabId: finalActiveId })
           saveTabContentDebounced(welcomeTab.id, welcomeTab.content)
         }
       }
@@ -366,7 +433,7 @@ export const useTabStore = create<TabState>((set, get) => ({
         tabs: [...state.tabs, newTab],
         activeTabId: newTab.id
       }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
 
       // Save content to IndexedDB
       if (newTab.content) {
@@ -385,9 +452,9 @@ export const useTabStore = create<TabState>((set, get) => ({
       contentSaveTimers.delete(id)
     }
 
-    // Delete from IndexedDB
-    indexedDBStorage.deleteTab(id).catch(err => {
-      console.error('Failed to delete tab from IndexedDB:', err)
+    // Delete stored content
+    sqliteStorage.deleteTab(id).catch(err => {
+      console.error('Failed to delete tab from storage:', err)
     })
 
     set((state) => {
@@ -396,7 +463,7 @@ export const useTabStore = create<TabState>((set, get) => ({
         ? (tabs[0]?.id || null)
         : state.activeTabId
       const newState = { ...state, tabs, activeTabId }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
       return { tabs, activeTabId }
     })
   },
@@ -404,7 +471,7 @@ export const useTabStore = create<TabState>((set, get) => ({
   setActiveTab: (id) => {
     set((state) => {
       const newState = { ...state, activeTabId: id }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
       return { activeTabId: id }
     })
   },
@@ -415,7 +482,7 @@ export const useTabStore = create<TabState>((set, get) => ({
         t.id === id ? { ...t, ...updates } : t
       )
       const newState = { ...state, tabs }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
 
       // If content is being updated, save to IndexedDB with debounce
       if (updates.content !== undefined) {
@@ -437,7 +504,7 @@ export const useTabStore = create<TabState>((set, get) => ({
     set((state) => {
       const viewSettings = { ...state.viewSettings, ...settings }
       const newState = { ...state, viewSettings }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
       return { viewSettings }
     })
   },
@@ -448,7 +515,7 @@ export const useTabStore = create<TabState>((set, get) => ({
       const [movedTab] = newTabs.splice(fromIndex, 1)
       newTabs.splice(toIndex, 0, movedTab)
       const newState = { ...state, tabs: newTabs }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
       return { tabs: newTabs }
     })
   },
@@ -459,7 +526,7 @@ export const useTabStore = create<TabState>((set, get) => ({
       const filtered = state.recentFiles.filter(f => f !== filePath)
       const recentFiles = [filePath, ...filtered].slice(0, 10) // Keep max 10
       const newState = { ...state, recentFiles }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
       return { recentFiles }
     })
   },
@@ -467,7 +534,7 @@ export const useTabStore = create<TabState>((set, get) => ({
   clearRecentFiles: () => {
     set((state) => {
       const newState = { ...state, recentFiles: [] }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
       return { recentFiles: [] }
     })
   },
@@ -491,7 +558,7 @@ export const useTabStore = create<TabState>((set, get) => ({
   setOpenFolderPath: (path) => {
     set((state) => {
       const newState = { ...state, openFolderPath: path }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
       return { openFolderPath: path }
     })
   },
@@ -510,7 +577,7 @@ export const useTabStore = create<TabState>((set, get) => ({
     set((state) => {
       const pinnedTabs = [...state.pinnedTabs, newPin]
       const newState = { ...state, pinnedTabs }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
       return { pinnedTabs }
     })
   },
@@ -519,7 +586,7 @@ export const useTabStore = create<TabState>((set, get) => ({
     set((state) => {
       const pinnedTabs = state.pinnedTabs.filter(p => p.id !== id)
       const newState = { ...state, pinnedTabs }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
       return { pinnedTabs }
     })
   },
@@ -530,7 +597,7 @@ export const useTabStore = create<TabState>((set, get) => ({
         p.id === id ? { ...p, ...updates } : p
       )
       const newState = { ...state, pinnedTabs }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
       return { pinnedTabs }
     })
   },
@@ -541,7 +608,7 @@ export const useTabStore = create<TabState>((set, get) => ({
       const [movedPin] = newPins.splice(fromIndex, 1)
       newPins.splice(toIndex, 0, movedPin)
       const newState = { ...state, pinnedTabs: newPins }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
       return { pinnedTabs: newPins }
     })
   },
@@ -552,7 +619,7 @@ export const useTabStore = create<TabState>((set, get) => ({
         p.id === id ? { ...p, isHidden: !p.isHidden } : p
       )
       const newState = { ...state, pinnedTabs }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
       return { pinnedTabs }
     })
   },
@@ -564,7 +631,7 @@ export const useTabStore = create<TabState>((set, get) => ({
         idSet.has(p.id) ? { ...p, isHidden } : p
       )
       const newState = { ...state, pinnedTabs }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
       return { pinnedTabs }
     })
   },
@@ -589,7 +656,7 @@ export const useTabStore = create<TabState>((set, get) => ({
       }
 
       const newState = { ...state, pinnedCategoryOrder: newOrder }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
       return { pinnedCategoryOrder: newOrder }
     })
   },
@@ -604,7 +671,7 @@ export const useTabStore = create<TabState>((set, get) => ({
       }
       const newCollapsed = Array.from(current)
       const newState = { ...state, pinnedCollapsedCategories: newCollapsed }
-      saveMetadataToLocalStorage(newState)
+      saveMetadata(newState)
       return { pinnedCollapsedCategories: newCollapsed }
     })
   }
@@ -1,5 +1,6 @@
 import { create } from 'zustand'
 import { extractTemplateVariables } from '../utils/templateVariables'
+import { sqliteStorage } from '../services/storage/SqliteStorage'
 
 export interface Template {
   id: string
@@ -80,7 +81,7 @@ const extractVariables = extractTemplateVariables
 // Load persisted templates
 const loadPersistedTemplates = (): Template[] => {
   try {
-    const saved = localStorage.getItem(STORAGE_KEY_TEMPLATES)
+    const saved = sqliteStorage.getItem(STORAGE_KEY_TEMPLATES)
     if (saved) {
       return JSON.parse(saved) as Template[]
     }
@@ -93,7 +94,7 @@ const loadPersistedTemplates = (): Template[] => {
 // Load persisted UI state
 const loadPersistedUI = (): PersistedUIState => {
   try {
-    const saved = localStorage.getItem(STORAGE_KEY_UI)
+    const saved = sqliteStorage.getItem(STORAGE_KEY_UI)
     if (saved) {
       return JSON.parse(saved)
     }
@@ -105,7 +106,7 @@ const loadPersistedUI = (): PersistedUIState => {
 
 const persistTemplates = (templates: Template[]) => {
   try {
-    localStorage.setItem(STORAGE_KEY_TEMPLATES, JSON.stringify(templates))
+    sqliteStorage.setItem(STORAGE_KEY_TEMPLATES, JSON.stringify(templates))
   } catch (error) {
     console.error('Failed to persist templates:', error)
   }
@@ -113,7 +114,7 @@ const persistTemplates = (templates: Template[]) => {
 
 const persistUI = (state: PersistedUIState) => {
   try {
-    localStorage.setItem(STORAGE_KEY_UI, JSON.stringify(state))
+    sqliteStorage.setItem(STORAGE_KEY_UI, JSON.stringify(state))
   } catch (error) {
     console.error('Failed to persist template UI state:', error)
   }
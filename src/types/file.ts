@@ -0,0 +1,218 @@
+/**
+ * File Types - Shapes returned by the Rust file commands
+ */
+
+export type LineEnding = 'lf' | 'crlf' | 'cr'
+
+/**
+ * Decoded file returned by read_file, with the format needed to save it back unchanged
+ */
+export interface FileContent {
+  content: string
+  encoding: string
+  has_bom: boolean
+  line_ending: LineEnding
+}
+
+/**
+ * Why read_file failed; binaries are refused rather than decoded as text
+ */
+export type ReadFileError =
+  | { kind: 'binary'; path: string; size: number; mime: string; message: string }
+  | { kind: 'io'; path: string; message: string }
+
+/**
+ * Result of detect_language; confidence ranges from 0 to 1
+ */
+export interface LanguageDetection {
+  language: string
+  confidence: number
+  source: 'modeline' | 'filename' | 'extension' | 'shebang' | 'content' | 'default'
+}
+
+/**
+ * Returned by write_file; content is set when save-time EditorConfig rules changed the text
+ */
+export interface WriteResult {
+  content: string | null
+  line_ending: LineEnding | null
+  hash: string
+}
+
+/**
+ * Effective .editorconfig properties for a file, from get_editor_config
+ */
+export interface EditorConfig {
+  indent_style: 'tab' | 'space' | null
+  indent_size: number | null
+  tab_width: number | null
+  end_of_line: LineEnding | null
+  charset: string | null
+  trim_trailing_whitespace: boolean | null
+  insert_final_newline: boolean | null
+  max_line_length: number | null
+}
+
+/**
+ * A stored snapshot of a file, from list_revisions (ids are millisecond timestamps)
+ */
+export interface Revision {
+  id: number
+  timestamp: number
+  hash: string
+  size: number
+  source: 'save' | 'autosave' | 'restore'
+}
+
+/**
+ * One run of a three-way merge, from merge_file_changes
+ */
+export type MergeHunk =
+  | { type: 'clean'; text: string }
+  | { type: 'conflict'; base: string; ours: string; theirs: string }
+
+export interface MergeResult {
+  merged: string | null
+  hunks: MergeHunk[]
+  conflicts: number
+  theirs: string
+}
+
+/**
+ * One side of a compute_diff comparison
+ */
+export type DiffInput = { text: string } | { path: string }
+
+export interface DiffOptions {
+  ignore_whitespace?: boolean
+  ignore_case?: boolean
+  context_lines?: number
+  lines_only?: boolean
+  unified?: boolean
+  old_label?: string
+  new_label?: string
+}
+
+export type DiffKind = 'equal' | 'insert' | 'delete'
+
+export interface DiffLine {
+  kind: DiffKind
+  // Zero-based line numbers in the old and new text
+  old_line: number | null
+  new_line: number | null
+  text: string
+  words?: { kind: DiffKind; text: string }[]
+}
+
+export interface DiffHunk {
+  old_start: number
+  old_lines: number
+  new_start: number
+  new_lines: number
+  lines: DiffLine[]
+}
+
+export interface DiffResult {
+  identical: boolean
+  insertions: number
+  deletions: number
+  hunks: DiffHunk[]
+  unified?: string
+}
+
+export type GitFileState = 'ignored' | 'untracked' | 'added' | 'renamed' | 'deleted' | 'modified' | 'conflicted'
+
+export interface GitFileStatus {
+  state: GitFileState
+  // Some of the change is staged
+  staged: boolean
+}
+
+/**
+ * Status of the entries of one directory, from git_status
+ */
+export interface GitDirectoryStatus {
+  root: string
+  branch: string | null
+  // Keyed by the paths read_directory returns; clean entries are absent
+  entries: Record<string, GitFileStatus>
+}
+
+export interface GitChange {
+  path: string
+  relative_path: string
+  state: GitFileState
+  staged: boolean
+}
+
+export interface GitCommitInfo {
+  id: string
+  short_id: string
+  summary: string
+  message: string
+  author: string
+  email: string
+  // Author time in milliseconds
+  timestamp: number
+}
+
+/**
+ * Lines of a file last changed by one commit, from git_blame
+ */
+export interface BlameRange {
+  // One-based
+  start_line: number
+  lines: number
+  // null for uncommitted lines
+  commit_id: string | null
+  original_path: string | null
+  original_start_line: number
+}
+
+export interface BlameResult {
+  ranges: BlameRange[]
+  commits: Record<string, GitCommitInfo>
+}
+
+export interface FileLogEntry extends GitCommitInfo {
+  // Path in this commit, relative to the repository root; pass it to
+  // git_file_at_revision as pathAtRevision
+  path: string
+}
+
+/**
+ * Files to open from the command line, queued as `open_files`
+ */
+export interface OpenFileRequest {
+  path: string
+  // One-based cursor position
+  line: number | null
+  column: number | null
+}
+
+export interface OpenRequest {
+  files: OpenFileRequest[]
+  readonly: boolean
+  // Open another tab even when the file is already open
+  new_tab: boolean
+  diff: { old: string; new: string } | null
+}
+
+/**
+ * A command-line path that couldn't be opened, queued as `open_files_failed`
+ */
+export interface OpenFailure {
+  path: string
+  reason: string
+}
+
+/**
+ * Something queued for the window to open, from take_pending_open_requests
+ */
+export type PendingOpen =
+  | { type: 'open_files'; request: OpenRequest }
+  | { type: 'open_files_failed'; failures: OpenFailure[] }
+  | { type: 'new_tab'; title: string | null; content: string }
+  | { type: 'template'; id: string; variables: Record<string, string> }
+  | { type: 'invalid_link'; url: string; reason: string }
+  | { type: 'invalid_arguments'; reason: string }
@@ -7,6 +7,23 @@ import { css } from '@codemirror/lang-css'
 import { yaml } from '@codemirror/lang-yaml'
 import { languages } from '@codemirror/language-data'
 import type { Extension } from '@codemirror/state'
+import { invoke } from '@tauri-apps/api/core'
+import type { LanguageDetection } from '../types/file'
+
+// detect_language only looks at the head of the file
+const DETECTION_SAMPLE_CHARS = 8192
+
+/**
+ * Detect a file's language from its path plus the start of its content
+ * (well-known names, shebangs, modelines and content heuristics)
+ */
+export async function detectLanguage(path: string, content: string): Promise<string> {
+  const detection = await invoke<LanguageDetection>('detect_language', {
+    path,
+    content: content.slice(0, DETECTION_SAMPLE_CHARS)
+  })
+  return detection.language
+}
 
 /**
  * Get the appropriate CodeMirror language extension based on the language identifier
@@ -56,9 +73,19 @@ export function getLanguageExtension(language: string): Extension {
 
     case 'text':
     case 'txt':
+    case 'log':
+    case 'tsv':
+    case 'ini':
+    case 'dotenv':
+    case 'ignore':
+    case 'dockerfile':
+    case 'makefile':
       // Plain text, no highlighting
       return []
 
+    case 'jsonl':
+      return json()
+
     default:
       // Default to markdown
       return markdown({
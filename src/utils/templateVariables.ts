@@ -52,3 +52,17 @@ export function extractTemplateVariables(content: string): string[] {
   const variables = Array.from(matches, m => m[1].trim())
   return [...new Set(variables)]
 }
+
+/**
+ * Replace named {{placeholders}} with the given values
+ * Placeholders without a value are left for the user to fill in
+ */
+export function fillTemplateVariables(
+  content: string,
+  values: Record<string, string>
+): string {
+  return content.replace(/\{\{([^}]+)\}\}/g, (placeholder, name: string) => {
+    const key = name.trim()
+    return Object.prototype.hasOwnProperty.call(values, key) ? values[key] : placeholder
+  })
+}